@@ -0,0 +1,57 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use cycle_loader::{exector::fn_executor, manager::Manager};
+
+// `layers` layers of `width` exectors, every exector depends on the whole previous layer
+fn layered_manager(layers: usize, width: usize) -> Manager {
+    let mut manager = Manager::new(60_000);
+    for layer in 0..layers {
+        for index in 0..width {
+            let name = format!("n{}_{}", layer, index);
+            manager.add_exector(fn_executor(name.clone(), || async { Ok(()) }));
+            if layer > 0 {
+                let deps: Vec<_> = (0..width)
+                    .map(|dep| format!("n{}_{}", layer - 1, dep))
+                    .collect();
+                manager.add_deps(name, deps);
+            }
+        }
+    }
+    manager
+}
+
+fn bench_run(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("run");
+    for (layers, width) in [(2000, 1), (200, 10), (40, 50)] {
+        let mut manager = layered_manager(layers, width);
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{}x{}", layers, width)),
+            |b| b.iter(|| runtime.block_on(manager.run()).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+// the same tiny exectors spawned as tasks and polled inline on the run loop
+fn bench_inline(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("inline");
+    for inline in [false, true] {
+        let mut manager = layered_manager(200, 10);
+        if inline {
+            for layer in 0..200 {
+                for index in 0..10 {
+                    manager.set_inline(format!("n{}_{}", layer, index));
+                }
+            }
+        }
+        let mode = if inline { "inline" } else { "spawn" };
+        group.bench_function(BenchmarkId::from_parameter(mode), |b| {
+            b.iter(|| runtime.block_on(manager.run()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_run, bench_inline);
+criterion_main!(benches);
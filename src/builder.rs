@@ -0,0 +1,76 @@
+use faststr::FastStr;
+
+use crate::{exector::Executor, manager::Manager, middlerware::Middlerware};
+
+// collect everything first, then build and validate the manager once
+pub struct ManagerBuilder {
+    timeout_ms: u64,
+    exectors: Vec<Box<dyn Executor>>,
+    edges: Vec<(FastStr, FastStr)>,
+    middlerwares: Vec<Middlerware>,
+}
+
+impl ManagerBuilder {
+    pub fn new(timeout_ms: u64) -> Self {
+        Self {
+            timeout_ms,
+            exectors: Vec::new(),
+            edges: Vec::new(),
+            middlerwares: Vec::new(),
+        }
+    }
+
+    pub fn timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn executor(mut self, exector: Box<dyn Executor>) -> Self {
+        self.exectors.push(exector);
+        self
+    }
+
+    pub fn executors(mut self, exectors: Vec<Box<dyn Executor>>) -> Self {
+        self.exectors.extend(exectors);
+        self
+    }
+
+    pub fn edge(mut self, from: impl Into<FastStr>, to: impl Into<FastStr>) -> Self {
+        self.edges.push((from.into(), to.into()));
+        self
+    }
+
+    pub fn dep(self, name: impl Into<FastStr>, dep: impl Into<FastStr>) -> Self {
+        self.edge(dep, name)
+    }
+
+    pub fn deps<T: Into<FastStr>>(mut self, name: impl Into<FastStr>, deps: Vec<T>) -> Self {
+        let name = name.into();
+        self.edges
+            .extend(deps.into_iter().map(|dep| (dep.into(), name.clone())));
+        self
+    }
+
+    // middlerwares wrap each other in order, the last added one is the outermost
+    pub fn middleware(mut self, middlerware: Middlerware) -> Self {
+        self.middlerwares.push(middlerware);
+        self
+    }
+
+    // fail on repeated exector names, dangling edges, cycles and unreachable exectors
+    pub fn build(self) -> anyhow::Result<Manager> {
+        let mut manager = Manager::new(self.timeout_ms);
+        for exector in self.exectors {
+            manager.try_add_exector(exector)?;
+        }
+        for (from, to) in self.edges {
+            manager.add_edge(from, to);
+        }
+        for middlerware in self.middlerwares {
+            manager.add_middlerware(middlerware);
+        }
+
+        manager.validate()?;
+        Ok(manager)
+    }
+}
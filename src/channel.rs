@@ -0,0 +1,291 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use tokio::sync::{mpsc, Notify};
+
+// a `stall_timeout` for `OverflowPolicy::Block` channels whose receiver may be read only after
+// the run
+pub const DEFAULT_BLOCK_STALL_TIMEOUT: Duration = Duration::from_secs(1);
+
+// what a bounded event channel does with a new event once it holds `capacity` events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    // the run waits for the consumer: no exector starts while the channel is full, and the
+    // events of a finished exector that do not fit are held back until there is room. progress
+    // an exector reports itself can not wait and is dropped while the channel is full.
+    // with `stall_timeout: Some(t)`, once the receiver takes nothing for `t` the held events are
+    // dropped and the channel acts like `DropEvent` until the receiver takes an event again, so a
+    // receiver read only after the run does not hang it. with `None` the run waits until the
+    // receiver takes an event or is dropped, however long that is
+    Block { stall_timeout: Option<Duration> },
+    // the oldest queued event is dropped to make room
+    DropOldest,
+    // the new event is dropped
+    DropEvent,
+}
+
+#[derive(Debug)]
+struct Queue<T> {
+    // what the receiver reads, never more than the capacity
+    events: VecDeque<T>,
+    // `Block` events of the run loop waiting for room, moved over as the receiver takes events
+    held: VecDeque<T>,
+    // `Block` gave up waiting for the receiver
+    stalled: bool,
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    queue: Mutex<Queue<T>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: AtomicU64,
+    senders: AtomicUsize,
+    receiver_alive: AtomicBool,
+    // an event was queued or the last sender left
+    readable: Notify,
+    // an event was taken or the receiver left
+    writable: Notify,
+}
+
+pub(crate) fn bounded<T>(
+    capacity: usize,
+    policy: OverflowPolicy,
+) -> (EventSender<T>, EventReceiver<T>) {
+    assert!(capacity > 0, "event channel capacity must be above 0");
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(Queue {
+            events: VecDeque::with_capacity(capacity),
+            held: VecDeque::new(),
+            stalled: false,
+        }),
+        capacity,
+        policy,
+        dropped: AtomicU64::new(0),
+        senders: AtomicUsize::new(1),
+        receiver_alive: AtomicBool::new(true),
+        readable: Notify::new(),
+        writable: Notify::new(),
+    });
+    (
+        EventSender {
+            shared: shared.clone(),
+        },
+        EventReceiver { shared },
+    )
+}
+
+#[derive(Debug)]
+pub(crate) struct EventSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Clone for EventSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::SeqCst);
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for EventSender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.shared.readable.notify_one();
+        }
+    }
+}
+
+impl<T> EventSender<T> {
+    // for the run loop, never waits. with `Block` an event that does not fit is held back and
+    // `wait_for_room` waits until it is queued
+    pub(crate) fn send(&self, event: T) {
+        self.push(event, true);
+    }
+
+    // for running exectors, which can not wait for room, so with `Block` an event that does
+    // not fit is dropped
+    pub(crate) fn send_from_exector(&self, event: T) {
+        self.push(event, false);
+    }
+
+    fn push(&self, event: T, hold: bool) {
+        if !self.shared.receiver_alive.load(Ordering::SeqCst) {
+            return;
+        }
+        let mut queue = self.shared.queue.lock().unwrap();
+        let blocking = matches!(self.shared.policy, OverflowPolicy::Block { .. }) && !queue.stalled;
+        // held events go first, nothing overtakes them
+        let full = queue.events.len() >= self.shared.capacity || !queue.held.is_empty();
+        if full {
+            match self.shared.policy {
+                OverflowPolicy::Block { .. } if blocking && hold => {
+                    queue.held.push_back(event);
+                    return;
+                }
+                OverflowPolicy::DropOldest => {
+                    queue.events.pop_front();
+                    self.shared.dropped.fetch_add(1, Ordering::SeqCst);
+                }
+                _ => {
+                    self.shared.dropped.fetch_add(1, Ordering::SeqCst);
+                    return;
+                }
+            }
+        }
+        queue.events.push_back(event);
+        drop(queue);
+        self.shared.readable.notify_one();
+    }
+
+    // false while a `Block` channel is full, the run loop starts no exector then
+    pub(crate) fn has_room(&self) -> bool {
+        let queue = self.shared.queue.lock().unwrap();
+        !matches!(self.shared.policy, OverflowPolicy::Block { .. })
+            || queue.stalled
+            || !self.shared.receiver_alive.load(Ordering::SeqCst)
+            || (queue.held.is_empty() && queue.events.len() < self.shared.capacity)
+    }
+
+    // with `Block`, until nothing is held back and there is room for the next event, nobody
+    // reads any more, or the receiver took nothing for the `stall_timeout`
+    pub(crate) async fn wait_for_room(&self) {
+        let stall_timeout = match self.shared.policy {
+            OverflowPolicy::Block { stall_timeout } => stall_timeout,
+            _ => return,
+        };
+        loop {
+            let writable = self.shared.writable.notified();
+            tokio::pin!(writable);
+            // registered before the check so a take in between is not missed
+            writable.as_mut().enable();
+            if self.has_room() {
+                return;
+            }
+            let Some(stall_timeout) = stall_timeout else {
+                writable.await;
+                continue;
+            };
+            if tokio::time::timeout(stall_timeout, writable).await.is_err() {
+                let mut queue = self.shared.queue.lock().unwrap();
+                queue.stalled = true;
+                let held = queue.held.len() as u64;
+                queue.held.clear();
+                self.shared.dropped.fetch_add(held, Ordering::SeqCst);
+                return;
+            }
+        }
+    }
+}
+
+// the receiving end of `Manager::bounded_progress_channel` and `Manager::bounded_results_channel`
+#[derive(Debug)]
+pub struct EventReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> EventReceiver<T> {
+    // None once the queue is empty and the manager dropped the channel
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            if let Some(event) = self.try_recv() {
+                return Some(event);
+            }
+            if self.shared.senders.load(Ordering::SeqCst) == 0 {
+                // an event queued right before the last sender left
+                return self.try_recv();
+            }
+            self.shared.readable.notified().await;
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<T> {
+        let mut queue = self.shared.queue.lock().unwrap();
+        let event = queue.events.pop_front()?;
+        if let Some(held) = queue.held.pop_front() {
+            queue.events.push_back(held);
+        }
+        // read again, so `Block` waits for it again
+        queue.stalled = false;
+        drop(queue);
+        self.shared.writable.notify_waiters();
+        Some(event)
+    }
+
+    // events dropped by the policy so far
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::SeqCst)
+    }
+
+    pub fn len(&self) -> usize {
+        self.shared.queue.lock().unwrap().events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for EventReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_alive.store(false, Ordering::SeqCst);
+        self.shared.writable.notify_waiters();
+    }
+}
+
+// where the manager sends events, the plain channels are unbounded
+#[derive(Debug)]
+pub(crate) enum EventSink<T> {
+    Unbounded(mpsc::UnboundedSender<T>),
+    Bounded(EventSender<T>),
+}
+
+impl<T> Clone for EventSink<T> {
+    fn clone(&self) -> Self {
+        match self {
+            EventSink::Unbounded(sender) => EventSink::Unbounded(sender.clone()),
+            EventSink::Bounded(sender) => EventSink::Bounded(sender.clone()),
+        }
+    }
+}
+
+impl<T> EventSink<T> {
+    // nobody listening is fine
+    pub(crate) fn send(&self, event: T) {
+        match self {
+            EventSink::Unbounded(sender) => {
+                let _ = sender.send(event);
+            }
+            EventSink::Bounded(sender) => sender.send(event),
+        }
+    }
+
+    pub(crate) fn send_from_exector(&self, event: T) {
+        match self {
+            EventSink::Unbounded(sender) => {
+                let _ = sender.send(event);
+            }
+            EventSink::Bounded(sender) => sender.send_from_exector(event),
+        }
+    }
+
+    pub(crate) fn has_room(&self) -> bool {
+        match self {
+            EventSink::Unbounded(_) => true,
+            EventSink::Bounded(sender) => sender.has_room(),
+        }
+    }
+
+    pub(crate) async fn wait_for_room(&self) {
+        if let EventSink::Bounded(sender) = self {
+            sender.wait_for_room().await;
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use ahash::AHashMap;
+use faststr::FastStr;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+pub(crate) enum AdmissionError {
+    // the global limit is reached, no exector can be admitted
+    Global,
+    // the exector's group limit is reached, exectors of other groups can still be admitted
+    Group,
+    // the exector costs more than what is left of the resource budget, cheaper exectors can
+    // still be admitted
+    Resources,
+}
+
+// amounts of named resources, e.g. memory in MB or cpu units, that an exector costs or that
+// the exectors of a run may hold at once
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resources {
+    amounts: BTreeMap<FastStr, u32>,
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, resource: impl Into<FastStr>, amount: u32) -> Self {
+        self.amounts.insert(resource.into(), amount);
+        self
+    }
+
+    // 0 for a resource that is not set
+    pub fn get(&self, resource: &str) -> u32 {
+        self.amounts.get(resource).copied().unwrap_or(0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&FastStr, u32)> {
+        self.amounts
+            .iter()
+            .map(|(resource, amount)| (resource, *amount))
+    }
+}
+
+// semaphores for one run, permits are held by the spawned exector until it finishes
+pub(crate) struct Admission {
+    global: Option<Arc<Semaphore>>,
+    groups: AHashMap<FastStr, Arc<Semaphore>>,
+    // one permit per unit of the budget, resources without a budget are not tracked
+    resources: BTreeMap<FastStr, (u32, Arc<Semaphore>)>,
+}
+
+impl Admission {
+    pub(crate) fn new(
+        max_concurrency: Option<usize>,
+        group_limits: &AHashMap<FastStr, usize>,
+        resource_budget: &Resources,
+    ) -> Self {
+        Self {
+            global: max_concurrency.map(|limit| Arc::new(Semaphore::new(limit))),
+            groups: group_limits
+                .iter()
+                .map(|(group, limit)| (group.clone(), Arc::new(Semaphore::new(*limit))))
+                .collect(),
+            resources: resource_budget
+                .iter()
+                .map(|(resource, budget)| {
+                    let budget = budget.max(1);
+                    let semaphore = Arc::new(Semaphore::new(budget as usize));
+                    (resource.clone(), (budget, semaphore))
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn try_acquire(
+        &self,
+        group: Option<&FastStr>,
+        cost: Option<&Resources>,
+    ) -> Result<Vec<OwnedSemaphorePermit>, AdmissionError> {
+        let mut permits = Vec::new();
+        if let Some(global) = &self.global {
+            permits.push(
+                global
+                    .clone()
+                    .try_acquire_owned()
+                    .map_err(|_| AdmissionError::Global)?,
+            );
+        }
+
+        if let Some(semaphore) = group.and_then(|group| self.groups.get(group)) {
+            permits.push(
+                semaphore
+                    .clone()
+                    .try_acquire_owned()
+                    .map_err(|_| AdmissionError::Group)?,
+            );
+        }
+
+        for (resource, amount) in cost.into_iter().flat_map(Resources::iter) {
+            let Some((budget, semaphore)) = self.resources.get(resource) else {
+                continue;
+            };
+            // a cost above the budget takes all of it, so the exector runs alone instead of never
+            let amount = amount.min(*budget);
+            if amount == 0 {
+                continue;
+            }
+            permits.push(
+                semaphore
+                    .clone()
+                    .try_acquire_many_owned(amount)
+                    .map_err(|_| AdmissionError::Resources)?,
+            );
+        }
+
+        Ok(permits)
+    }
+}
@@ -0,0 +1,83 @@
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+use ahash::AHashMap;
+use dashmap::DashMap;
+
+// the values a scoped context started with, see `Context::write_back`
+pub(crate) type ContextSnapshot = AHashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+// type map shared by every exector of one run, each type holds at most one value
+#[derive(Default)]
+pub struct Context {
+    values: DashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // insert a value, replace and return the old one of the same type
+    pub fn insert<T: Any + Send + Sync>(&self, value: T) -> Option<Arc<T>> {
+        self.values
+            .insert(TypeId::of::<T>(), Arc::new(value))
+            .and_then(|old| old.downcast().ok())
+    }
+
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.clone().downcast().ok())
+    }
+
+    pub fn contains<T: Any + Send + Sync>(&self) -> bool {
+        self.values.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn remove<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.values
+            .remove(&TypeId::of::<T>())
+            .and_then(|(_, value)| value.downcast().ok())
+    }
+
+    // a context sharing every value but the one of type T, e.g. for a scoped view
+    pub fn without<T: Any + Send + Sync>(&self) -> Context {
+        let values = self
+            .values
+            .iter()
+            .filter(|entry| *entry.key() != TypeId::of::<T>())
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
+        Self { values }
+    }
+
+    pub(crate) fn snapshot(&self) -> ContextSnapshot {
+        self.values
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect()
+    }
+
+    // copy the values inserted or replaced here since `started` into `shared`. a value the
+    // scope started with and never touched is skipped, so its stale copy never overwrites what
+    // another exector inserted in the meantime
+    pub(crate) fn write_back(&self, started: &ContextSnapshot, shared: &Context) {
+        for entry in self.values.iter() {
+            let untouched = started
+                .get(entry.key())
+                .is_some_and(|value| Arc::ptr_eq(value, entry.value()));
+            if !untouched {
+                shared.values.insert(*entry.key(), entry.value().clone());
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
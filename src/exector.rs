@@ -1,15 +1,198 @@
-
-use std::sync::Arc;
+use std::{
+    fmt,
+    future::Future,
+    sync::{Arc, Mutex},
+};
 
 use async_trait::async_trait;
+use faststr::FastStr;
+
+use crate::{context::Context, progress::ProgressReporter};
+
+// any error type works, anyhow errors convert with `?`
+pub type ExectorError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type ExectorResult = Result<(), ExectorError>;
+
+// returned through the error of `execute` to steer the run instead of failing the node, so it
+// passes every wrapper and middlerware untouched. it is never retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeControl {
+    // the node is done and the exectors reached only through it are skipped. an exector with
+    // any dep outside the stopped branch still runs once its deps are done
+    StopBranch,
+}
+
+impl fmt::Display for NodeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeControl::StopBranch => write!(f, "stop branch"),
+        }
+    }
+}
+
+impl std::error::Error for NodeControl {}
+
+// end the node and prune the branch below it, e.g. when there is no new data to transform
+pub fn stop_branch() -> ExectorResult {
+    Err(Box::new(NodeControl::StopBranch))
+}
 
 #[async_trait]
 pub trait Executor: Send + Sync {
-    async fn execute(&self) -> anyhow::Result<()>;
-    fn name(&self) -> &'static str;
+    async fn execute(&self, ctx: &Context) -> ExectorResult;
+    fn name(&self) -> FastStr;
+
+    // the entry point of the manager, long running exectors override it to report how far
+    // they got, the rest only implement `execute`
+    async fn execute_with_progress(
+        &self,
+        ctx: &Context,
+        _progress: &ProgressReporter,
+    ) -> ExectorResult {
+        self.execute(ctx).await
+    }
+
+    // exectors this one depends on, merged with the deps added on the manager
+    fn deps(&self) -> Vec<FastStr> {
+        Vec::new()
+    }
+
+    // a key of the inputs, when it equals the key of the last successful run and no dep
+    // ran again, the exector is skipped as cached
+    fn cache_key(&self) -> Option<u64> {
+        None
+    }
 }
 
+/// Wrap an exector for shared ownership, feed it to `Manager::add_shared_exector`.
+/// `box_executor` is the owned counterpart for `Manager::add_exector`.
+///
+/// ```
+/// use async_trait::async_trait;
+/// use cycle_loader::{
+///     context::Context,
+///     exector::{box_executor, exector_wapper, Executor, ExectorResult},
+///     manager::Manager,
+/// };
+/// use faststr::FastStr;
+///
+/// struct Hello(&'static str);
+///
+/// #[async_trait]
+/// impl Executor for Hello {
+///     async fn execute(&self, _ctx: &Context) -> ExectorResult {
+///         Ok(())
+///     }
+///
+///     fn name(&self) -> FastStr {
+///         self.0.into()
+///     }
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let mut manager = Manager::new(1000);
+/// manager.add_shared_exector(exector_wapper(Hello("shared")));
+/// manager.add_exector(box_executor(Hello("owned")));
+/// manager.add_dep("owned", "shared");
+/// manager.run().await.unwrap();
+/// # });
+/// ```
 pub fn exector_wapper<T: Executor + 'static>(executor: T) -> Arc<dyn Executor> {
     Arc::new(executor)
 }
 
+pub fn box_executor<T: Executor + 'static>(executor: T) -> Box<dyn Executor> {
+    Box::new(executor)
+}
+
+// an exector registered under a node name of its own, so one exector can sit at several
+// places in the graph
+struct AliasExector {
+    id: FastStr,
+    inner: Arc<dyn Executor>,
+}
+
+#[async_trait]
+impl Executor for AliasExector {
+    async fn execute(&self, ctx: &Context) -> ExectorResult {
+        self.inner.execute(ctx).await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        ctx: &Context,
+        progress: &ProgressReporter,
+    ) -> ExectorResult {
+        self.inner.execute_with_progress(ctx, progress).await
+    }
+
+    fn name(&self) -> FastStr {
+        self.id.clone()
+    }
+
+    fn deps(&self) -> Vec<FastStr> {
+        self.inner.deps()
+    }
+
+    fn cache_key(&self) -> Option<u64> {
+        self.inner.cache_key()
+    }
+}
+
+pub(crate) fn alias(id: FastStr, exector: Arc<dyn Executor>) -> Arc<dyn Executor> {
+    Arc::new(AliasExector { id, inner: exector })
+}
+
+struct FnExector<F> {
+    name: FastStr,
+    f: F,
+}
+
+#[async_trait]
+impl<F, Fut> Executor for FnExector<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = ExectorResult> + Send,
+{
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        (self.f)().await
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.clone()
+    }
+}
+
+// an exector from an async closure, called again on every run
+pub fn fn_executor<F, Fut>(name: impl Into<FastStr>, f: F) -> Box<dyn Executor>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ExectorResult> + Send + 'static,
+{
+    Box::new(FnExector {
+        name: name.into(),
+        f,
+    })
+}
+
+// an exector from a closure that can only be called once, later runs fail the node
+pub fn fn_once_executor<F, Fut>(name: impl Into<FastStr>, f: F) -> Box<dyn Executor>
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = ExectorResult> + Send + 'static,
+{
+    let name = name.into();
+    let once = Mutex::new(Some(f));
+    let exector_name = name.clone();
+    fn_executor(name, move || {
+        let f = once.lock().unwrap().take();
+        let name = exector_name.clone();
+        async move {
+            match f {
+                Some(f) => f().await,
+                None => Err(format!("exector {} already executed", name).into()),
+            }
+        }
+    })
+}
@@ -0,0 +1,90 @@
+use std::{
+    panic,
+    sync::{
+        atomic::{AtomicI64, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use tokio::task::JoinHandle;
+
+use crate::{
+    manager::{Manager, RunError},
+    report::RunReport,
+};
+
+// counters the run loop keeps up to date while exectors finish
+#[derive(Debug, Default)]
+pub(crate) struct Liveness {
+    completed: AtomicUsize,
+    in_flight: AtomicUsize,
+    // 0 until the first completion
+    last_completion_micros: AtomicI64,
+}
+
+impl Liveness {
+    pub(crate) fn reset(&self) {
+        self.completed.store(0, Ordering::SeqCst);
+        self.in_flight.store(0, Ordering::SeqCst);
+        self.last_completion_micros.store(0, Ordering::SeqCst);
+    }
+
+    pub(crate) fn spawned(&self) {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn completed(&self, now_micros: i64) {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.completed.fetch_add(1, Ordering::SeqCst);
+        self.last_completion_micros
+            .store(now_micros, Ordering::SeqCst);
+    }
+}
+
+// a run moved onto its own task by `Manager::spawn_run`. the probes are Send and Sync and never
+// wait on the run, so a health check can poll them from any thread. each value is read
+// atomically on its own, two probes in a row may see different moments of the run.
+// a stalled run keeps `in_flight_count` above 0 while `last_completion_time` stops moving,
+// a slow one keeps moving it
+pub struct RunHandle {
+    liveness: Arc<Liveness>,
+    task: JoinHandle<(Manager, Result<RunReport, RunError>)>,
+}
+
+impl RunHandle {
+    pub(crate) fn new(
+        liveness: Arc<Liveness>,
+        task: JoinHandle<(Manager, Result<RunReport, RunError>)>,
+    ) -> Self {
+        Self { liveness, task }
+    }
+
+    // exectors that finished executing, failed ones included, skipped ones not
+    pub fn completed_count(&self) -> usize {
+        self.liveness.completed.load(Ordering::SeqCst)
+    }
+
+    // exectors executing right now
+    pub fn in_flight_count(&self) -> usize {
+        self.liveness.in_flight.load(Ordering::SeqCst)
+    }
+
+    // micros of the manager clock, like `NodeTiming`, when an exector last finished
+    pub fn last_completion_time(&self) -> Option<i64> {
+        match self.liveness.last_completion_micros.load(Ordering::SeqCst) {
+            0 => None,
+            micros => Some(micros),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    // wait for the run and get the manager back with its result, a panic of the run resumes here
+    pub async fn join(self) -> (Manager, Result<RunReport, RunError>) {
+        self.task
+            .await
+            .unwrap_or_else(|err| panic::resume_unwind(err.into_panic()))
+    }
+}
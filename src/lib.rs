@@ -1,4 +1,18 @@
+pub mod builder;
+pub mod channel;
+mod concurrency;
+pub mod context;
 pub mod exector;
+pub mod handle;
 pub mod manager;
+#[cfg(feature = "prometheus")]
+mod metrics;
 pub mod middlerware;
-mod tracing_info;
+pub mod nodes;
+pub mod once;
+pub mod progress;
+pub mod report;
+pub mod retry;
+pub mod scheduler;
+pub mod spec;
+pub mod tracing_info;
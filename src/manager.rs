@@ -1,46 +1,736 @@
+use std::{
+    any::Any,
+    cmp::Reverse,
+    collections::{BTreeMap, VecDeque},
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use ahash::{AHashMap, AHashSet};
-use futures::future;
-use tokio::task::JoinHandle;
+use faststr::FastStr;
+use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
+use tokio::{
+    runtime::Handle,
+    sync::{mpsc, OwnedSemaphorePermit},
+    task::{self, AbortHandle, JoinSet},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, Span};
 
-use crate::{exector::Executor, middlerware::Middlerware, tracing_info::TracingInfoManager};
+#[cfg(feature = "prometheus")]
+use crate::metrics::Metrics;
+use crate::{
+    builder::ManagerBuilder,
+    channel::{self, EventReceiver, EventSink, OverflowPolicy},
+    concurrency::{Admission, AdmissionError},
+    context::Context,
+    exector::{self, fn_executor, ExectorError, ExectorResult, Executor, NodeControl},
+    handle::{Liveness, RunHandle},
+    middlerware::{self, Middlerware, MiddlerwareSetupError, TryMiddlerware},
+    once::{self, OnceRegistry},
+    progress::{self, ProgressEvent},
+    report::{NodeOutcome, Outcome, RunReport, RunStats},
+    retry::{Backoff, RetryPolicy},
+    scheduler,
+    spec::GraphSpec,
+    tracing_info::{AttemptInfo, Clock, NodeTiming, RunCounts, Status, TracingInfoManager},
+};
+
+mod chrome_trace;
+mod critical_path;
+mod dot;
+mod graph;
+
+pub use crate::concurrency::Resources;
+pub use graph::{GraphStats, NodeView};
+
+// called in the scheduling loop for every finished exector, keep it cheap
+pub type NodeCompleteHook = Box<dyn Fn(&str, NodeOutcome) + Send + Sync>;
+
+// run time limit of `Manager::default`, one minute
+pub const DEFAULT_TIMEOUT_MS: u64 = 60_000;
+
+// decides at scheduling time whether an exector runs
+pub type Condition = Box<dyn Fn(&Context) -> bool + Send + Sync>;
+
+// like `Condition`, but awaited in the exector's task once its concurrency slots are taken,
+// an error is handled by `ConditionErrorMode`
+pub type AsyncCondition = Box<
+    dyn Fn(Arc<Context>) -> Pin<Box<dyn Future<Output = Result<bool, ExectorError>> + Send>>
+        + Send
+        + Sync,
+>;
+
+// derives the context an exector sees from the shared one
+pub type ContextScope = Box<dyn Fn(&Context) -> Context + Send + Sync>;
+
+// why a run failed, converts into `anyhow::Error` with `?`. the errors of a run that got
+// going carry the exectors it completed with their durations, sorted by name
+#[derive(Debug)]
+pub enum RunError {
+    // the time limit or deadline of the run passed, running exectors were aborted
+    Timeout {
+        elapsed_ms: u64,
+        in_progress: Vec<FastStr>,
+        not_started: Vec<FastStr>,
+        finished: usize,
+        completed: Vec<(FastStr, Duration)>,
+    },
+    // an exector failed in `StopOnError` mode, with the error it returned
+    NodeFailed {
+        name: FastStr,
+        source: ExectorError,
+        completed: Vec<(FastStr, Duration)>,
+    },
+    // the nodes on a cycle, the first node is repeated at the end
+    Cycle(Vec<FastStr>),
+    // the graph or the nodes asked to run are not valid
+    Validation(String),
+    // the manager has no exectors
+    EmptyGraph,
+    // the entry nodes are set but empty. when every exector has deps the cycle is reported
+    NoStartNodes,
+    // the token given to `run_with_cancel` was cancelled
+    Cancelled {
+        completed: Vec<(FastStr, Duration)>,
+    },
+    // another run of the same manager is in progress
+    AlreadyRunning,
+    // nothing was left running but these exectors never got all their deps, each with the
+    // deps it still waits for. a scheduling bug, reported rather than returning a partial run
+    Stalled {
+        stuck: Vec<(FastStr, Vec<FastStr>)>,
+        completed: Vec<(FastStr, Duration)>,
+    },
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::Timeout {
+                elapsed_ms,
+                in_progress,
+                not_started,
+                finished,
+                ..
+            } => write!(
+                f,
+                "run timeout after {}ms, running: [{}], not started: [{}], finished: {}",
+                elapsed_ms,
+                in_progress.join(", "),
+                not_started.join(", "),
+                finished
+            ),
+            RunError::NodeFailed { name, .. } => write!(f, "exector {} failed", name),
+            RunError::Cycle(path) if path.len() == 2 && path[0] == path[1] => {
+                write!(f, "find cycle: exector {} depends on itself", path[0])
+            }
+            RunError::Cycle(path) => write!(f, "find cycle: {}", path.join(" -> ")),
+            RunError::Validation(message) => write!(f, "{}", message),
+            RunError::EmptyGraph => write!(f, "no exectors to run"),
+            RunError::NoStartNodes => write!(f, "no start nodes, the entry nodes are empty"),
+            RunError::Cancelled { .. } => write!(f, "run cancelled"),
+            RunError::AlreadyRunning => write!(f, "run already in progress"),
+            RunError::Stalled { stuck, .. } => {
+                let stuck: Vec<String> = stuck
+                    .iter()
+                    .map(|(name, deps)| format!("{} waits for [{}]", name, deps.join(", ")))
+                    .collect();
+                write!(f, "run stalled, stuck exectors: {}", stuck.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for RunError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RunError::NodeFailed { source, .. } => Some(&**source),
+            _ => None,
+        }
+    }
+}
+
+impl RunError {
+    // what the run completed before it stopped, empty when it never started
+    pub fn completed(&self) -> &[(FastStr, Duration)] {
+        match self {
+            RunError::Timeout { completed, .. }
+            | RunError::NodeFailed { completed, .. }
+            | RunError::Cancelled { completed }
+            | RunError::Stalled { completed, .. } => completed,
+            _ => &[],
+        }
+    }
+}
+
+// guards against runaway graphs, unbounded by default
+struct GraphLimits {
+    max_nodes: usize,
+    max_edges: usize,
+    max_fan_out: usize,
+}
+
+impl Default for GraphLimits {
+    fn default() -> Self {
+        Self {
+            max_nodes: usize::MAX,
+            max_edges: usize::MAX,
+            max_fan_out: usize::MAX,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FailureMode {
+    // log the error and keep scheduling as if the exector succeeded
+    #[default]
+    ContinueOnError,
+    // abort every running exector and return the first error
+    StopOnError,
+    // keep running independent exectors, but skip every exector whose dep failed or was skipped,
+    // except over `DepKind::OnComplete` edges
+    SkipOnDepFailure,
+}
+
+// when a dep lets its dependent run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepKind {
+    // only once the dep succeeded, a failed dep skips the dependent like `SkipOnDepFailure`
+    OnSuccess,
+    // once the dep finished, failed or not, even with `SkipOnDepFailure`
+    OnComplete,
+}
+
+// what an async condition that returns an error does to its node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConditionErrorMode {
+    // the node fails with the error, it is not retried
+    #[default]
+    Fail,
+    // the node is skipped as if the condition said no
+    Skip,
+}
 
 pub struct Manager {
     // base field
     timeout_ms: u64,
-    adjacency_list: AHashMap<&'static str, Vec<&'static str>>,
-    rev_adjacency_list: AHashMap<&'static str, Vec<&'static str>>,
-    exectors: AHashMap<&'static str, Box<dyn Executor>>,
+    failure_mode: FailureMode,
+    entry_nodes: Option<Vec<FastStr>>,
+    max_concurrency: Option<usize>,
+    span_enabled: bool,
+    log_summary: bool,
+    concurrency_groups: AHashMap<FastStr, FastStr>,
+    group_limits: AHashMap<FastStr, usize>,
+    costs: AHashMap<FastStr, Resources>,
+    resource_budget: Resources,
+    adjacency_list: AHashMap<FastStr, Vec<FastStr>>,
+    rev_adjacency_list: AHashMap<FastStr, Vec<FastStr>>,
+    edge_count: usize,
+    // optional deps by the dep they wait for, turned into edges while the dep is registered
+    optional_deps: AHashMap<FastStr, Vec<FastStr>>,
+    // kinds set per edge by dependent and dep, other edges follow the failure mode
+    dep_kinds: AHashMap<FastStr, AHashMap<FastStr, DepKind>>,
+    start_delays: AHashMap<FastStr, Duration>,
+    non_critical: AHashSet<FastStr>,
+    branch_tokens: AHashMap<FastStr, CancellationToken>,
+    expected_durations: AHashMap<FastStr, Duration>,
+    budget_timeouts: bool,
+    // node timeouts split from the time limit of the current run
+    budgeted_timeouts: AHashMap<FastStr, u64>,
+    // set when a run starts
+    run_id: FastStr,
+    liveness: Arc<Liveness>,
+    // in the order the run loop saw the failures
+    first_error: Option<(FastStr, String)>,
+    // spawn onto this runtime instead of the ambient one
+    runtime: Option<Handle>,
+    // a run started at least once, checked on drop
+    ran: bool,
+    warn_unrun_drop: bool,
+    limits: GraphLimits,
+    exectors: AHashMap<FastStr, Arc<dyn Executor>>,
+    // added through the `Scheduler` during the last run
+    scheduled_exectors: Vec<FastStr>,
+    node_timeouts: AHashMap<FastStr, u64>,
+    blocking_exectors: AHashSet<FastStr>,
+    inline_exectors: AHashSet<FastStr>,
+    once_registries: AHashMap<FastStr, Arc<OnceRegistry>>,
 
     // for extension feild
-    middlerware: Option<Middlerware>,
+    middlerwares: Vec<Arc<Middlerware>>,
+    retry_policies: AHashMap<FastStr, RetryPolicy>,
+    conditions: AHashMap<FastStr, Condition>,
+    async_conditions: AHashMap<FastStr, Arc<AsyncCondition>>,
+    condition_error_mode: ConditionErrorMode,
+    context_scopes: AHashMap<FastStr, ContextScope>,
+    priorities: AHashMap<FastStr, i32>,
+    tenants: AHashMap<FastStr, FastStr>,
+    // soft edges by the exector that prefers to go later
+    hints: AHashMap<FastStr, Vec<FastStr>>,
+    node_meta: AHashMap<FastStr, BTreeMap<FastStr, String>>,
+    node_complete_hook: Option<NodeCompleteHook>,
+    progress_sender: Option<EventSink<ProgressEvent>>,
+    results_sender: Option<EventSink<(FastStr, NodeOutcome)>>,
+    #[cfg(feature = "prometheus")]
+    metrics: Option<Metrics>,
 
     // inner field
+    running: Arc<AtomicBool>,
+    // cache key of every exector at its last successful run
+    cache_keys: AHashMap<FastStr, u64>,
     _tracing: TracingInfoManager,
+    context: Arc<Context>,
+}
+
+impl Default for Manager {
+    fn default() -> Self {
+        Self::new(DEFAULT_TIMEOUT_MS)
+    }
 }
 
 impl Manager {
+    // timeout_ms bounds every run, 0 or u64::MAX runs without a time limit
     pub fn new(timeout_ms: u64) -> Self {
         Self {
             timeout_ms,
+            failure_mode: FailureMode::default(),
+            entry_nodes: None,
+            max_concurrency: None,
+            span_enabled: false,
+            log_summary: false,
+            concurrency_groups: AHashMap::new(),
+            group_limits: AHashMap::new(),
+            costs: AHashMap::new(),
+            resource_budget: Resources::new(),
             adjacency_list: AHashMap::new(),
             rev_adjacency_list: AHashMap::new(),
+            edge_count: 0,
+            optional_deps: AHashMap::new(),
+            dep_kinds: AHashMap::new(),
+            start_delays: AHashMap::new(),
+            non_critical: AHashSet::new(),
+            branch_tokens: AHashMap::new(),
+            expected_durations: AHashMap::new(),
+            budget_timeouts: false,
+            budgeted_timeouts: AHashMap::new(),
+            run_id: FastStr::empty(),
+            liveness: Arc::new(Liveness::default()),
+            first_error: None,
+            runtime: None,
+            ran: false,
+            warn_unrun_drop: true,
+            limits: GraphLimits::default(),
             exectors: AHashMap::new(),
-            middlerware: None,
+            node_timeouts: AHashMap::new(),
+            blocking_exectors: AHashSet::new(),
+            inline_exectors: AHashSet::new(),
+            once_registries: AHashMap::new(),
+            middlerwares: Vec::new(),
+            retry_policies: AHashMap::new(),
+            conditions: AHashMap::new(),
+            async_conditions: AHashMap::new(),
+            condition_error_mode: ConditionErrorMode::default(),
+            context_scopes: AHashMap::new(),
+            priorities: AHashMap::new(),
+            tenants: AHashMap::new(),
+            hints: AHashMap::new(),
+            node_meta: AHashMap::new(),
+            node_complete_hook: None,
+            progress_sender: None,
+            results_sender: None,
+            #[cfg(feature = "prometheus")]
+            metrics: None,
+            running: Arc::new(AtomicBool::new(false)),
+            cache_keys: AHashMap::new(),
+            scheduled_exectors: Vec::new(),
             _tracing: TracingInfoManager::new(),
+            context: Arc::new(Context::new()),
         }
     }
 
+    pub fn builder(timeout_ms: u64) -> ManagerBuilder {
+        ManagerBuilder::new(timeout_ms)
+    }
+
+    // replace every added middlerware with this one
     pub fn set_middlerware(&mut self, middlerware: Middlerware) {
-        self.middlerware = Some(middlerware);
+        self.middlerwares = vec![Arc::new(middlerware)];
+    }
+
+    // middlerwares wrap each other in order, the last added one is the outermost
+    pub fn add_middlerware(&mut self, middlerware: Middlerware) {
+        self.middlerwares.push(Arc::new(middlerware));
+    }
+
+    // like `add_middlerware`, a failed setup fails the node without running the exector
+    pub fn add_try_middlerware(&mut self, try_middlerware: TryMiddlerware) {
+        self.add_middlerware(middlerware::from_try(try_middlerware));
+    }
+
+    pub fn set_failure_mode(&mut self, failure_mode: FailureMode) {
+        self.failure_mode = failure_mode;
+    }
+
+    // start only from these exectors instead of every exector without deps,
+    // exectors not reachable from them never run
+    pub fn set_entry_nodes<T: Into<FastStr>>(&mut self, entry_nodes: Vec<T>) {
+        self.entry_nodes = Some(entry_nodes.into_iter().map(Into::into).collect());
+    }
+
+    // per node tracing is on by default. off, only `run_counts` is kept, which saves memory on
+    // very large graphs: reports, timings and the summary come out empty or as counts
+    pub fn set_tracing(&mut self, enabled: bool) {
+        self._tracing.set_enabled(enabled, self.exectors.keys());
+    }
+
+    // wrap each run in a `run` span and each exector in a child `executor` span,
+    // off by default so the spans cost nothing without a subscriber that wants them
+    pub fn set_span_enabled(&mut self, span_enabled: bool) {
+        self.span_enabled = span_enabled;
+    }
+
+    // register node duration, failure and in-flight metrics in the registry,
+    // every later run updates them
+    #[cfg(feature = "prometheus")]
+    pub fn with_metrics_registry(&mut self, registry: &prometheus::Registry) -> anyhow::Result<()> {
+        self.metrics = Some(Metrics::register(registry)?);
+        Ok(())
+    }
+
+    // spawn the exectors of every run, and the run of `spawn_run`, onto this runtime, e.g. a
+    // dedicated one for cpu bound graphs. the run loop stays on the runtime that awaits it
+    pub fn set_runtime(&mut self, runtime: Handle) {
+        self.runtime = Some(runtime);
+    }
+
+    // applies from the next run, 0 or u64::MAX runs without a time limit
+    pub fn set_timeout_ms(&mut self, timeout_ms: u64) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    // a manager with exectors that is dropped before any run warns, turn it off when discarding
+    // one on purpose, e.g. after `validate` or `to_dot`
+    pub fn set_warn_unrun_drop(&mut self, warn_unrun_drop: bool) {
+        self.warn_unrun_drop = warn_unrun_drop;
+    }
+
+    // also log the tracing of every exector as one human readable line after each run,
+    // the per node and per run events carry their data as fields either way
+    pub fn set_log_summary(&mut self, log_summary: bool) {
+        self.log_summary = log_summary;
     }
 
+    // cap how many exectors run at the same time, 0 is treated as 1
+    pub fn set_max_concurrency(&mut self, max_concurrency: usize) {
+        self.max_concurrency = Some(max_concurrency.max(1));
+    }
+
+    // exectors of a group share the group limit, exectors without a group are only bound by the global limit
+    pub fn set_concurrency_group(&mut self, name: impl Into<FastStr>, group: impl Into<FastStr>) {
+        self.concurrency_groups.insert(name.into(), group.into());
+    }
+
+    // 0 is treated as 1, a group without limit is unconstrained
+    pub fn set_group_limit(&mut self, group: impl Into<FastStr>, limit: usize) {
+        self.group_limits.insert(group.into(), limit.max(1));
+    }
+
+    // what the exector holds of the resource budget while it runs
+    pub fn set_cost(&mut self, name: impl Into<FastStr>, cost: Resources) {
+        self.costs.insert(name.into(), cost);
+    }
+
+    // the sum of the costs of running exectors stays within the budget, a ready exector that
+    // does not fit waits while cheaper ones behind it start. a budget of 0 is treated as 1,
+    // resources without a budget are unconstrained. replaces the previous budget
+    pub fn set_resource_budget(&mut self, budget: Resources) {
+        self.resource_budget = budget;
+    }
+
+    // retry a failed exector until it succeeds or max_attempts is reached
+    pub fn set_retry(&mut self, name: impl Into<FastStr>, max_attempts: u32, backoff: Backoff) {
+        self.retry_policies.insert(
+            name.into(),
+            RetryPolicy {
+                max_attempts,
+                backoff,
+            },
+        );
+    }
+
+    // called right after each exector finishes and its tracing is recorded, also for exectors
+    // short circuited, skipped by an async condition or cancelled with a branch. exectors
+    // skipped before they start, by a sync condition, a cached result or a failed or stopped
+    // dep, are not reported
+    pub fn on_node_complete(&mut self, hook: NodeCompleteHook) {
+        self.node_complete_hook = Some(hook);
+    }
+
+    // events of every later run go to the returned receiver, a new call replaces it.
+    // dropping the receiver does not affect the run
+    pub fn progress_channel(&mut self) -> mpsc::UnboundedReceiver<ProgressEvent> {
+        let (progress_sender, progress_receiver) = mpsc::unbounded_channel();
+        self.progress_sender = Some(EventSink::Unbounded(progress_sender));
+        progress_receiver
+    }
+
+    // like `progress_channel`, holding at most `capacity` events that nobody read yet, the
+    // policy decides what happens to the next ones. panics on a capacity of 0
+    pub fn bounded_progress_channel(
+        &mut self,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> EventReceiver<ProgressEvent> {
+        let (progress_sender, progress_receiver) = channel::bounded(capacity, policy);
+        self.progress_sender = Some(EventSink::Bounded(progress_sender));
+        progress_receiver
+    }
+
+    // the outcome of every finished exector of later runs in completion order, a new call
+    // replaces the receiver. dropping the receiver does not affect the run
+    pub fn results_channel(&mut self) -> mpsc::UnboundedReceiver<(FastStr, NodeOutcome)> {
+        let (results_sender, results_receiver) = mpsc::unbounded_channel();
+        self.results_sender = Some(EventSink::Unbounded(results_sender));
+        results_receiver
+    }
+
+    // like `results_channel`, bounded like `bounded_progress_channel`
+    pub fn bounded_results_channel(
+        &mut self,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> EventReceiver<(FastStr, NodeOutcome)> {
+        let (results_sender, results_receiver) = channel::bounded(capacity, policy);
+        self.results_sender = Some(EventSink::Bounded(results_sender));
+        results_receiver
+    }
+
+    // when the concurrency limits make ready exectors wait, higher priorities are spawned first,
+    // exectors default to 0 and equal priorities keep the order they became ready in
+    pub fn set_priority(&mut self, name: impl Into<FastStr>, priority: i32) {
+        self.priorities.insert(name.into(), priority);
+    }
+
+    // prefer to start `to` after `from` when both are ready with the same priority. a hint only
+    // orders exectors that wait at the same time, it never makes one wait for another, so it
+    // is left out of dependency and cycle checks and hints in a cycle can not deadlock a run
+    pub fn add_hint(&mut self, from: impl Into<FastStr>, to: impl Into<FastStr>) {
+        let (from, to) = (from.into(), to.into());
+        let befores = self.hints.entry(to).or_default();
+        if !befores.contains(&from) {
+            befores.push(from);
+        }
+    }
+
+    // when ready exectors wait for slots, tenants take turns at the free slots so one tenant
+    // can not starve the others. priorities order the exectors within a tenant, not across
+    // tenants. exectors without a tenant share one unnamed tenant
+    pub fn set_tenant(&mut self, name: impl Into<FastStr>, tenant: impl Into<FastStr>) {
+        self.tenants.insert(name.into(), tenant.into());
+    }
+
+    // bound the graph size, adding an exector or edge past a limit panics
+    pub fn set_limits(&mut self, max_nodes: usize, max_edges: usize) {
+        self.limits.max_nodes = max_nodes;
+        self.limits.max_edges = max_edges;
+    }
+
+    // bound how many exectors may depend directly on a single one
+    pub fn set_max_fan_out(&mut self, max_fan_out: usize) {
+        self.limits.max_fan_out = max_fan_out;
+    }
+
+    // free form labels such as the owning team, shown in `tracing_snapshot` and `to_dot`,
+    // they never affect scheduling
+    pub fn set_node_meta(
+        &mut self,
+        name: impl Into<FastStr>,
+        key: impl Into<FastStr>,
+        value: impl Into<String>,
+    ) {
+        self.node_meta
+            .entry(name.into())
+            .or_default()
+            .insert(key.into(), value.into());
+    }
+
+    // a best effort exector, its failure is logged and reported as `Outcome::FailedNonCritical`
+    // but never stops the run, not even with `StopOnError`, and its next exectors treat it as
+    // completed whatever their failure mode or `DepKind`
+    pub fn set_non_critical(&mut self, name: impl Into<FastStr>) {
+        self.non_critical.insert(name.into());
+    }
+
+    // hold the exector back this long once it is ready, a crude throttle for bursts. the delay
+    // counts as wait in tracing, the exector takes its concurrency slots only after it
+    pub fn set_start_delay(&mut self, name: impl Into<FastStr>, delay: Duration) {
+        self.start_delays.insert(name.into(), delay);
+    }
+
+    // bound a single exector, on expiry the node fails alone and the run goes on
+    pub fn set_node_timeout(&mut self, name: impl Into<FastStr>, timeout_ms: u64) {
+        self.node_timeouts.insert(name.into(), timeout_ms);
+    }
+
+    // how long the exector usually takes, used by `set_budget_timeouts`
+    pub fn set_expected_duration(&mut self, name: impl Into<FastStr>, expected: Duration) {
+        self.expected_durations.insert(name.into(), expected);
+    }
+
+    // split the run time limit into node timeouts along the chains of expected durations, so
+    // a slow exector fails by itself with a node timeout instead of the whole run timing out.
+    // an exector without an expected duration or with `set_node_timeout` keeps its own limit,
+    // and the flat run time limit stays in force on top
+    pub fn set_budget_timeouts(&mut self, budget_timeouts: bool) {
+        self.budget_timeouts = budget_timeouts;
+    }
+
+    // checked once all deps of the exector are done, against the context of the run.
+    // when it returns false the exector is marked skipped and its next exectors treat it as
+    // satisfied, so a skipped entry node does not block its subtree. only a failed dep blocks
+    // the subtree, and only with `SkipOnDepFailure`
+    pub fn set_condition(&mut self, name: impl Into<FastStr>, condition: Condition) {
+        self.conditions.insert(name.into(), condition);
+    }
+
+    // like `set_condition` for checks that have to wait, e.g. on a flag service. it is awaited
+    // once the exector got its concurrency slots, so it shows as Doing in tracing meanwhile, and
+    // a false result ends the node as Skipped. errors follow `set_condition_error_mode`
+    pub fn set_async_condition(&mut self, name: impl Into<FastStr>, condition: AsyncCondition) {
+        self.async_conditions
+            .insert(name.into(), Arc::new(condition));
+    }
+
+    pub fn set_condition_error_mode(&mut self, condition_error_mode: ConditionErrorMode) {
+        self.condition_error_mode = condition_error_mode;
+    }
+
+    // poll the exector on the run loop instead of spawning a task for it, which saves the task
+    // for glue exectors that return at once. only for genuinely cheap exectors: while it runs
+    // it holds up the scheduling of every other exector and never runs in parallel with them
+    pub fn set_inline(&mut self, name: impl Into<FastStr>) {
+        self.inline_exectors.insert(name.into());
+    }
+
+    // run the exector on the blocking thread pool, for cpu heavy or synchronous work that would
+    // hold up the async workers. a timeout or abort can not stop it once it started
+    pub fn set_blocking(&mut self, name: impl Into<FastStr>) {
+        self.blocking_exectors.insert(name.into());
+    }
+
+    // execute the exector at most once successfully across every manager sharing the registry,
+    // see `OnceRegistry`. later schedulings finish at once without executing it
+    pub fn set_run_once(&mut self, name: impl Into<FastStr>, registry: Arc<OnceRegistry>) {
+        self.once_registries.insert(name.into(), registry);
+    }
+
+    // the exector and its middlerwares see the context made by the scope instead of the shared
+    // one, e.g. `ctx.without::<Secret>()`. what it inserts is copied back to the shared context
+    // once it finishes, so its output still reaches its dependents
+    pub fn set_context_scope(&mut self, name: impl Into<FastStr>, scope: ContextScope) {
+        self.context_scopes.insert(name.into(), scope);
+    }
+
+    pub fn has_exector(&self, name: &str) -> bool {
+        self.exectors.contains_key(name)
+    }
+
+    // panics on a repeated name or when the node limit is reached, see `try_add_exector`
     pub fn add_exector(&mut self, exector: Box<dyn Executor>) {
-        if self.exectors.contains_key(exector.name()) {
-            panic!("exector name repeat: {}", exector.name());
+        self.add_shared_exector(Arc::from(exector));
+    }
+
+    // same as `add_exector` for an exector that is shared, e.g. from `exector_wapper`
+    pub fn add_shared_exector(&mut self, exector: Arc<dyn Executor>) {
+        if let Err(err) = self.try_add_shared_exector(exector) {
+            panic!("{}", err);
         }
+    }
+
+    // register the exector under `id` instead of its own name, the node is known by `id` in
+    // edges, settings, tracing and reports. the same exector can be added under several ids
+    pub fn add_exector_as(&mut self, id: impl Into<FastStr>, exector: Box<dyn Executor>) {
+        self.add_shared_exector_as(id, Arc::from(exector));
+    }
+
+    pub fn add_shared_exector_as(&mut self, id: impl Into<FastStr>, exector: Arc<dyn Executor>) {
+        self.add_shared_exector(exector::alias(id.into(), exector));
+    }
+
+    pub fn try_add_exector(&mut self, exector: Box<dyn Executor>) -> anyhow::Result<()> {
+        self.try_add_shared_exector(Arc::from(exector))
+    }
+
+    pub fn try_add_shared_exector(&mut self, exector: Arc<dyn Executor>) -> anyhow::Result<()> {
+        let name = exector.name();
+        if self.exectors.contains_key(&name) {
+            return Err(anyhow::anyhow!("exector name repeat: {}", name));
+        }
+        if self.exectors.len() >= self.limits.max_nodes {
+            return Err(anyhow::anyhow!(
+                "exector {} exceeds the limit of {} exectors",
+                name,
+                self.limits.max_nodes
+            ));
+        }
+        self.check_limits(&self.declared_edges(&name, &exector))?;
+
+        self.insert_exector(name, exector);
+        Ok(())
+    }
+
+    // swap the exector registered under the same name and return the old one. edges and per
+    // node settings of the name are kept, deps declared by the new exector are added to them.
+    // like `add_exector` it panics when a new name reaches the node limit or the declared deps
+    // break an edge limit, nothing is changed then
+    pub fn add_or_replace_exector(
+        &mut self,
+        exector: Box<dyn Executor>,
+    ) -> Option<Arc<dyn Executor>> {
+        let exector: Arc<dyn Executor> = Arc::from(exector);
+        let name = exector.name();
+        if !self.exectors.contains_key(&name) {
+            self.add_shared_exector(exector);
+            return None;
+        }
+
+        if let Err(err) = self.check_limits(&self.declared_edges(&name, &exector)) {
+            panic!("{}", err);
+        }
+        self.insert_exector(name, exector)
+    }
 
-        self.exectors.insert(exector.name(), exector);
+    // the edges `insert_exector` adds for the exector, its declared deps may not exist yet
+    fn declared_edges(
+        &self,
+        name: &FastStr,
+        exector: &Arc<dyn Executor>,
+    ) -> Vec<(FastStr, FastStr)> {
+        let deps = exector.deps().into_iter().map(|dep| (dep, name.clone()));
+        let dependents = self.optional_deps.get(name).into_iter().flatten();
+        deps.chain(dependents.map(|dependent| (name.clone(), dependent.clone())))
+            .collect()
+    }
+
+    fn insert_exector(
+        &mut self,
+        name: FastStr,
+        exector: Arc<dyn Executor>,
+    ) -> Option<Arc<dyn Executor>> {
+        self.add_deps(name.clone(), exector.deps());
+        for dependent in self.optional_deps.get(&name).cloned().unwrap_or_default() {
+            self.add_edge(name.clone(), dependent);
+        }
+        self._tracing.add_tracing_info(name.clone());
+        self.exectors.insert(name, exector)
     }
 
     pub fn add_exectors(&mut self, exectors: Vec<Box<dyn Executor>>) {
@@ -49,178 +739,1985 @@ impl Manager {
         }
     }
 
-    pub fn add_edge(&mut self, from: &'static str, to: &'static str) {
-        if self.adjacency_list.contains_key(from) {
-            if self.adjacency_list[from].contains(&to) {
-                return;
+    pub fn add_edge(&mut self, from: impl Into<FastStr>, to: impl Into<FastStr>) {
+        let (from, to) = (from.into(), to.into());
+        // check both lists so a half recorded edge is completed instead of skipped
+        let to_list = self.adjacency_list.entry(from.clone()).or_default();
+        if !to_list.contains(&to) {
+            if self.edge_count >= self.limits.max_edges {
+                panic!(
+                    "edge {} -> {} exceeds the limit of {} edges",
+                    from, to, self.limits.max_edges
+                );
             }
+            if to_list.len() >= self.limits.max_fan_out {
+                panic!(
+                    "edge {} -> {} exceeds the fan out limit of {}",
+                    from, to, self.limits.max_fan_out
+                );
+            }
+            to_list.push(to.clone());
+            self.edge_count += 1;
         }
+        let from_list = self.rev_adjacency_list.entry(to).or_default();
+        if !from_list.contains(&from) {
+            from_list.push(from);
+        }
+    }
 
-        self.adjacency_list
-            .entry(from)
-            .or_insert_with(Vec::new)
-            .push(to);
-        self.rev_adjacency_list
-            .entry(to)
-            .or_insert_with(Vec::new)
-            .push(from);
+    // returns whether the edge existed
+    pub fn remove_edge(&mut self, from: &str, to: &str) -> bool {
+        let removed_forward = remove_from_list(&mut self.adjacency_list, from, to);
+        if removed_forward {
+            self.edge_count -= 1;
+        }
+        let removed_backward = remove_from_list(&mut self.rev_adjacency_list, to, from);
+        if let Some(kinds) = self.dep_kinds.get_mut(to) {
+            kinds.remove(from);
+        }
+        removed_forward || removed_backward
     }
 
-    pub fn add_edges(&mut self, from: &'static str, to_list: Vec<&'static str>) {
-        for to in to_list {
-            self.add_edge(from, to);
+    // remove the exector with its edges and per node settings
+    pub fn remove_executor(&mut self, name: &str) -> Option<Arc<dyn Executor>> {
+        let exector = self.exectors.remove(name)?;
+        for to in self.adjacency_list.remove(name).unwrap_or_default() {
+            remove_from_list(&mut self.rev_adjacency_list, &to, name);
+            self.edge_count -= 1;
+        }
+        for from in self.rev_adjacency_list.remove(name).unwrap_or_default() {
+            if remove_from_list(&mut self.adjacency_list, &from, name) {
+                self.edge_count -= 1;
+            }
+        }
+
+        for dependents in self.optional_deps.values_mut() {
+            dependents.retain(|dependent| dependent != name);
+        }
+        self.optional_deps
+            .retain(|_, dependents| !dependents.is_empty());
+        self.dep_kinds.remove(name);
+        for kinds in self.dep_kinds.values_mut() {
+            kinds.remove(name);
         }
+        self.node_timeouts.remove(name);
+        self.blocking_exectors.remove(name);
+        self.inline_exectors.remove(name);
+        self.once_registries.remove(name);
+        self.retry_policies.remove(name);
+        self.conditions.remove(name);
+        self.async_conditions.remove(name);
+        self.start_delays.remove(name);
+        self.non_critical.remove(name);
+        self.branch_tokens.remove(name);
+        self.expected_durations.remove(name);
+        self.context_scopes.remove(name);
+        self.priorities.remove(name);
+        self.tenants.remove(name);
+        self.hints.remove(name);
+        for befores in self.hints.values_mut() {
+            befores.retain(|before| before != name);
+        }
+        self.node_meta.remove(name);
+        self.cache_keys.remove(name);
+        self.concurrency_groups.remove(name);
+        self.costs.remove(name);
+        self._tracing.remove_tracing_info(name);
+        Some(exector)
     }
 
-    pub fn add_dep(&mut self, name: &'static str, dep: &'static str) {
-        self.add_edge(dep, name);
+    pub fn add_edges<T: Into<FastStr>>(&mut self, from: impl Into<FastStr>, to_list: Vec<T>) {
+        let from = from.into();
+        for to in to_list {
+            self.add_edge(from.clone(), to);
+        }
     }
 
-    pub fn add_deps(&mut self, name: &'static str, deps: Vec<&'static str>) {
-        for dep in deps {
-            self.add_dep(name, dep);
-        }
-    }
-
-    pub async fn run(&mut self) -> anyhow::Result<()> {
-        tokio::time::timeout(
-            std::time::Duration::from_millis(self.timeout_ms),
-            self.run_inner(),
-        )
-        .await
-        .map_or_else(
-            |err| {
-                tracing::error!(
-                    "run timeout!!!, time limit is {} ms, err is {:?}",
-                    self.timeout_ms,
-                    err
-                );
-                tracing::error!("exector tracing info: {}", self._tracing);
-                Err(err.into())
-            },
-            |res| {
-                tracing::info!("exector tracing info: {}", self._tracing);
-                res
-            },
-        )
+    // like `add_edge`, but errors instead of adding an edge that uses an unknown exector, would
+    // close a cycle or break a limit. an edge that already exists is fine
+    pub fn try_add_edge(
+        &mut self,
+        from: impl Into<FastStr>,
+        to: impl Into<FastStr>,
+    ) -> anyhow::Result<()> {
+        let (from, to) = (from.into(), to.into());
+        self.check_edge(&from, &to)?;
+        self.add_edge(from, to);
+        Ok(())
     }
 
-    async fn run_inner(&mut self) -> anyhow::Result<()> {
-        let start_exectors = self.pre_check_and_find_start_nodes()?;
+    // add the edges one by one, the first bad edge is reported and the edges added before it
+    // are taken back, so the graph is left as it was
+    pub fn try_add_edges<T: Into<FastStr>>(
+        &mut self,
+        from: impl Into<FastStr>,
+        to_list: Vec<T>,
+    ) -> anyhow::Result<()> {
+        let from = from.into();
+        let edges = to_list.into_iter().map(|to| (from.clone(), to.into()));
+        self.try_add_all(edges.collect())
+    }
 
-        let mut handles: Vec<_> = start_exectors.iter().map(|exector_name| {
-            let exector = self.exectors.remove(exector_name).unwrap();
-            self._tracing.start(exector.name());
-            self.build_handle(exector)
-        }).collect();
+    // `try_add_edges` from every dep to the exector
+    pub fn try_add_deps<T: Into<FastStr>>(
+        &mut self,
+        name: impl Into<FastStr>,
+        deps: Vec<T>,
+    ) -> anyhow::Result<()> {
+        let name = name.into();
+        let edges = deps.into_iter().map(|dep| (dep.into(), name.clone()));
+        self.try_add_all(edges.collect())
+    }
 
-        let mut all_ready_exector_names = AHashSet::new();
-        while !handles.is_empty() {
-            let (ready_handle, _, remain_handles) = future::select_all(handles).await;
-            if ready_handle.is_err() {
-                // is not exector response error, is select all error, so panic
-                panic!("join handle error: {:?}", ready_handle);
+    fn try_add_all(&mut self, edges: Vec<(FastStr, FastStr)>) -> anyhow::Result<()> {
+        let mut added: Vec<(FastStr, FastStr)> = Vec::new();
+        for (from, to) in edges {
+            let exists = self
+                .adjacency_list
+                .get(&from)
+                .is_some_and(|to_list| to_list.contains(&to));
+            if let Err(err) = self.check_edge(&from, &to) {
+                for (from, to) in added {
+                    self.remove_edge(&from, &to);
+                }
+                return Err(err);
             }
+            self.add_edge(from.clone(), to.clone());
+            if !exists {
+                added.push((from, to));
+            }
+        }
+        Ok(())
+    }
 
-            let ready_exector_name = match ready_handle.unwrap() {
-                Ok(name) => name,
-                Err((name, err)) => {
-                    tracing::error!("exector {} error: {:?}", name, err);
+    fn check_edge(&self, from: &FastStr, to: &FastStr) -> anyhow::Result<()> {
+        for name in [from, to] {
+            if !self.exectors.contains_key(name) {
+                return Err(anyhow::anyhow!(
+                    "edge {} -> {} uses unknown exector {}",
+                    from,
+                    to,
                     name
-                }
-            };
-            all_ready_exector_names.insert(ready_exector_name);
-            self._tracing.done(ready_exector_name);
-
-            let mut new_handles = remain_handles;
-            if let Some(next_exector_names) =  self.adjacency_list.get(ready_exector_name) {
-                for next_exector_name in next_exector_names {
-                    if let Some(next_exector_deps) = self.rev_adjacency_list.get(next_exector_name) {
-                        if next_exector_deps.iter().all(|dep| all_ready_exector_names.contains(dep)) {
-                            let next_exector = self.exectors.remove(next_exector_name).unwrap();
-                            self._tracing.start(next_exector.name());
-                            new_handles.push(self.build_handle(next_exector));
-                        }
-                    }
-                }
+                ));
             }
+        }
+        let to_list = self.adjacency_list.get(from);
+        if to_list.is_some_and(|to_list| to_list.contains(to)) {
+            return Ok(());
+        }
+        if from == to {
+            return Err(anyhow::anyhow!("edge {} -> {} is a self loop", from, to));
+        }
+        if let Some(path) = self.find_path(to, from) {
+            let path: Vec<&str> = path.iter().map(FastStr::as_str).collect();
+            return Err(anyhow::anyhow!(
+                "edge {} -> {} closes the cycle {} -> {}",
+                from,
+                to,
+                path.join(" -> "),
+                to
+            ));
+        }
+        self.check_limits(&[(from.clone(), to.clone())])
+    }
 
-            handles = new_handles;
+    // errors when adding the edges would break the edge or fan out limit, an edge that exists
+    // already or repeats is counted once
+    fn check_limits(&self, edges: &[(FastStr, FastStr)]) -> anyhow::Result<()> {
+        let mut edge_count = self.edge_count;
+        let mut fan_outs: AHashMap<&FastStr, usize> = AHashMap::new();
+        let mut seen = AHashSet::new();
+        for (from, to) in edges {
+            let to_list = self.adjacency_list.get(from);
+            if to_list.is_some_and(|to_list| to_list.contains(to)) || !seen.insert((from, to)) {
+                continue;
+            }
+            if edge_count >= self.limits.max_edges {
+                return Err(anyhow::anyhow!(
+                    "edge {} -> {} exceeds the limit of {} edges",
+                    from,
+                    to,
+                    self.limits.max_edges
+                ));
+            }
+            let fan_out = fan_outs
+                .entry(from)
+                .or_insert_with(|| to_list.map_or(0, Vec::len));
+            if *fan_out >= self.limits.max_fan_out {
+                return Err(anyhow::anyhow!(
+                    "edge {} -> {} exceeds the fan out limit of {}",
+                    from,
+                    to,
+                    self.limits.max_fan_out
+                ));
+            }
+            *fan_out += 1;
+            edge_count += 1;
         }
+        Ok(())
+    }
 
+    // add every edge of the spec, nothing is added if an edge uses a node the spec does not declare
+    pub fn apply_spec(&mut self, spec: &GraphSpec) -> anyhow::Result<()> {
+        spec.check()?;
+        for (from, to) in &spec.edges {
+            self.add_edge(from.clone(), to.clone());
+        }
         Ok(())
+    }
 
+    pub fn add_dep(&mut self, name: impl Into<FastStr>, dep: impl Into<FastStr>) {
+        self.add_edge(dep, name);
     }
 
-    fn pre_check_and_find_start_nodes(&self) -> anyhow::Result<Vec<&'static str>> {
-        let start_nodes = self.find_start_nodes();
-        if start_nodes.is_empty() {
-            return Err(anyhow::anyhow!("no start nodes, maybe has cycle"));
+    // a dep whose failure is handled per edge instead of by the failure mode
+    pub fn add_dep_kind(
+        &mut self,
+        name: impl Into<FastStr>,
+        dep: impl Into<FastStr>,
+        kind: DepKind,
+    ) {
+        let (name, dep) = (name.into(), dep.into());
+        self.dep_kinds
+            .entry(name.clone())
+            .or_default()
+            .insert(dep.clone(), kind);
+        self.add_dep(name, dep);
+    }
+
+    pub fn add_deps<T: Into<FastStr>>(&mut self, name: impl Into<FastStr>, deps: Vec<T>) {
+        let name = name.into();
+        for dep in deps {
+            self.add_dep(name.clone(), dep);
         }
+    }
 
-        // check cycle
-        let mut visited = AHashMap::new();
-        let mut stack = Vec::new();
-        for start_node in start_nodes.iter() {
-            stack.push(start_node);
-            visited.insert(start_node, false);
+    // run after `dep` if it is an exector, an absent dep is ignored instead of failing the run.
+    // the edge exists while the dep is registered, whether it is added before or after this
+    // call, so `validate` and `run` see the exectors registered when they start
+    pub fn add_optional_dep(&mut self, name: impl Into<FastStr>, dep: impl Into<FastStr>) {
+        let (name, dep) = (name.into(), dep.into());
+        let dependents = self.optional_deps.entry(dep.clone()).or_default();
+        if dependents.contains(&name) {
+            return;
+        }
+        dependents.push(name.clone());
+        if self.exectors.contains_key(&dep) {
+            self.add_edge(dep, name);
         }
+    }
 
-        while let Some(node) = stack.pop() {
-            if let Some(neighbors) = self.adjacency_list.get(node) {
-                for neighbor in neighbors.iter() {
-                    if visited.get(neighbor).cloned().unwrap_or(false) {
-                        return Err(anyhow::anyhow!(
-                            "find cycle, please check {} to {}",
-                            node,
-                            neighbor
-                        ));
-                    } else {
-                        stack.push(neighbor);
-                        visited.insert(neighbor, false);
-                    }
-                }
-            }
+    // a no-op exector that runs once every node in `after` is done, a phase boundary
+    pub fn add_barrier<T: Into<FastStr>>(&mut self, name: impl Into<FastStr>, after: Vec<T>) {
+        let name = name.into();
+        self.add_exector(fn_executor(name.clone(), || async { Ok(()) }));
+        self.add_deps(name, after);
+    }
+
+    // same as `add_barrier`, and every node in `before` waits for the barrier
+    pub fn add_barrier_between<T: Into<FastStr>, U: Into<FastStr>>(
+        &mut self,
+        name: impl Into<FastStr>,
+        after: Vec<T>,
+        before: Vec<U>,
+    ) {
+        let name = name.into();
+        self.add_barrier(name.clone(), after);
+        self.add_edges(name, before);
+    }
+
+    // the context shared by the exectors of the latest run
+    pub fn context(&self) -> Arc<Context> {
+        self.context.clone()
+    }
+
+    // forget what the last run left behind: every node back to NotStarted in tracing, the run
+    // counts, the first error, the run id and an empty context. exectors, edges, per node
+    // settings and cached results stay, `clear_cache` drops the latter. a run does this itself
+    // when it starts, so this is only needed to inspect or reconfigure a clean manager between runs
+    pub fn reset(&mut self) {
+        self.remove_scheduled_exectors();
+        self._tracing.reset();
+        self.liveness.reset();
+        self.first_error = None;
+        self.run_id = FastStr::empty();
+        self.context = Arc::new(Context::new());
+    }
 
-            visited.insert(node, true);
+    fn remove_scheduled_exectors(&mut self) {
+        for name in std::mem::take(&mut self.scheduled_exectors) {
+            self.remove_executor(&name);
         }
+    }
 
-        Ok(start_nodes)
+    // forget every cached result, the next run executes everything
+    pub fn clear_cache(&mut self) {
+        self.cache_keys.clear();
     }
 
-    fn find_start_nodes(&self) -> Vec<&'static str> {
-        let mut start_nodes = Vec::new();
-        for (name, _) in self.exectors.iter() {
-            if !self.rev_adjacency_list.contains_key(name) {
-                start_nodes.push(name.clone());
-            }
+    // where tracing timestamps come from, the local wall clock by default
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self._tracing.set_clock(clock);
+    }
+
+    // timings of every node from the latest run, ordered by name
+    pub fn tracing_snapshot(&self) -> Vec<NodeTiming> {
+        self._tracing
+            .snapshot()
+            .into_iter()
+            .map(|timing| self.with_meta(timing))
+            .collect()
+    }
+
+    // how many nodes of the last run finished each way, also with tracing off
+    pub fn run_counts(&self) -> RunCounts {
+        self._tracing.counts()
+    }
+
+    // wall clock and summed node time of the latest run, finished nodes only
+    pub fn run_stats(&self) -> RunStats {
+        RunStats::from_timings(&self._tracing.snapshot())
+    }
+
+    pub fn node_timing(&self, name: &str) -> anyhow::Result<NodeTiming> {
+        self._tracing
+            .get_tracing_info(name)
+            .map(|tracing_info| self.with_meta(NodeTiming::new(FastStr::new(name), tracing_info)))
+    }
+
+    fn with_meta(&self, mut timing: NodeTiming) -> NodeTiming {
+        if let Some(meta) = self.node_meta.get(&timing.name) {
+            timing.meta = meta.clone();
         }
+        timing
+    }
 
-        start_nodes
+    // true from the start of a run until it returns or its future is dropped
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
     }
 
-    fn build_handle(&self, exector: Box<dyn Executor>) -> JoinHandle<Result<&'static str, (&'static str, anyhow::Error)>> {
-        let name = exector.name();
+    pub async fn run(&mut self) -> Result<(), RunError> {
+        self.run_with_report().await.map(|_| ())
+    }
 
-        if let Some(middlerware) = &self.middlerware {
-            let wrap_exector = middlerware(exector);
-            tokio::spawn(async move {
-                let res = wrap_exector.await;
-                if let Err(err) = res {
-                    Err((name, err))
-                } else {
-                    Ok(name)
-                }
-            })
-        } else {
-            tokio::spawn(async move {
-                let res = exector.execute().await;
-                if let Err(err) = res {
-                    Err((name, err))
-                } else {
-                    Ok(name)
-                }
-            })
+    pub async fn run_with_report(&mut self) -> Result<RunReport, RunError> {
+        self.run_within(self.time_limit(), None, None).await
+    }
+
+    // cancel the branch of `target` while the rest of the run goes on, e.g. once nobody wants
+    // its output any more. the branch is the target, every exector below it, and the ancestors
+    // that no other exector of the run needs: an ancestor is dropped once all of its next
+    // exectors are dropped, so a dep shared with a live exector keeps running. the target is
+    // aborted if it runs, an inline target is left to finish and its result ignored. ancestors
+    // that started already are never aborted, they finish and are reported as usual. the rest
+    // of the branch is reported as `Outcome::Cancelled` and never starts. a target that
+    // finished is left alone.
+    // the token applies to the run in progress, or the next one when cancelled between runs,
+    // and is dropped once that run ends. asking again gives the same token until then
+    pub fn branch_token(&mut self, target: impl Into<FastStr>) -> CancellationToken {
+        let token = self.branch_tokens.entry(target.into()).or_default();
+        if token.is_cancelled() {
+            *token = CancellationToken::new();
+        }
+        token.clone()
+    }
+
+    // like `run`, but stops early with `RunError::Cancelled` once the token is cancelled,
+    // the running exectors are aborted and nothing else is spawned
+    pub async fn run_with_cancel(&mut self, token: CancellationToken) -> Result<(), RunError> {
+        tokio::select! {
+            biased;
+            _ = token.cancelled() => {}
+            res = self.run() => return res,
+        }
+
+        tracing::warn!(run_id = %self.run_id, status = "cancelled", "run cancelled");
+        Err(RunError::Cancelled {
+            completed: RunReport::from_tracing(&self._tracing).completed_durations(),
+        })
+    }
+
+    // like `run`, but bounded by an absolute deadline instead of `timeout_ms`,
+    // a deadline already passed times out at once without spawning anything
+    pub async fn run_until(&mut self, deadline: Instant) -> Result<(), RunError> {
+        let time_limit = deadline.saturating_duration_since(Instant::now());
+        if time_limit.is_zero() {
+            tracing::error!(
+                status = "timeout",
+                time_limit_ms = 0u64,
+                "run deadline already passed"
+            );
+            let mut not_started: Vec<_> = self.exectors.keys().cloned().collect();
+            not_started.sort_unstable();
+            return Err(RunError::Timeout {
+                elapsed_ms: 0,
+                in_progress: Vec::new(),
+                not_started,
+                finished: 0,
+                completed: Vec::new(),
+            });
         }
+        self.run_within(Some(time_limit), None, None)
+            .await
+            .map(|_| ())
+    }
+
+    // run only what the target needs, like `make <target>`: the target and every exector
+    // it depends on, directly or not. errors if the target is not an exector
+    pub async fn run_target(&mut self, target: impl Into<FastStr>) -> Result<(), RunError> {
+        self.run_targets(vec![target]).await
+    }
+
+    // run the union of what every target needs, shared deps run once
+    pub async fn run_targets<T: Into<FastStr>>(&mut self, targets: Vec<T>) -> Result<(), RunError> {
+        let targets: Vec<FastStr> = targets.into_iter().map(Into::into).collect();
+        let scope = self.dependency_closure(&targets)?;
+        self.run_within(self.time_limit(), Some(scope), None)
+            .await
+            .map(|_| ())
+    }
+
+    // like `run_with_report`, with the id on every event and span of the run and on the
+    // report, to correlate the logs of one run across services
+    pub async fn run_labeled(&mut self, run_id: impl Into<FastStr>) -> Result<RunReport, RunError> {
+        self.run_within(self.time_limit(), None, Some(run_id.into()))
+            .await
+    }
+
+    // run on a new tokio task, the handle probes its progress and gives the manager back
+    pub fn spawn_run(mut self) -> RunHandle {
+        let liveness = self.liveness.clone();
+        let runtime = self.runtime.clone();
+        let run = async move {
+            let res = self.run_with_report().await;
+            (self, res)
+        };
+        let task = match runtime {
+            Some(runtime) => runtime.spawn(run),
+            None => tokio::spawn(run),
+        };
+        RunHandle::new(liveness, task)
+    }
+
+    // id of the current or last run, a run without a label gets a random one
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    // run only the exectors of one of `components`, the others are not scheduled at all, so a
+    // latency critical subgraph gets every concurrency slot to itself
+    pub async fn run_component(&mut self, index: usize) -> Result<(), RunError> {
+        let Some(component) = self.components().into_iter().nth(index) else {
+            return Err(RunError::Validation(format!(
+                "component {} out of range",
+                index
+            )));
+        };
+        let scope = component.into_iter().collect();
+        self.run_within(self.time_limit(), Some(scope), None)
+            .await
+            .map(|_| ())
+    }
+
+    // None runs without a time limit
+    async fn run_within(
+        &mut self,
+        time_limit: Option<Duration>,
+        scope: Option<AHashSet<FastStr>>,
+        run_id: Option<FastStr>,
+    ) -> Result<RunReport, RunError> {
+        let _running = RunningGuard::enter(&self.running)?;
+        self.ran = true;
+        self.budgeted_timeouts = match time_limit {
+            Some(time_limit) if self.budget_timeouts => {
+                // a cycle is reported by the run itself
+                self.budget_timeouts(time_limit).unwrap_or_default()
+            }
+            _ => AHashMap::new(),
+        };
+        self.run_id = run_id.unwrap_or_else(new_run_id);
+        let span = if self.span_enabled {
+            tracing::info_span!("run", run_id = %self.run_id)
+        } else {
+            Span::none()
+        };
+
+        let run = self.run_inner(scope).instrument(span);
+        let res = match time_limit {
+            // the exectors live in a join set owned by the run future, dropping it on timeout aborts them
+            Some(time_limit) => tokio::time::timeout(time_limit, run)
+                .await
+                .map_err(|_| time_limit),
+            None => Ok(run.await),
+        };
+        // a token cancelled during the run is spent, also when the run timed out
+        self.branch_tokens.retain(|_, token| !token.is_cancelled());
+        let res = match res {
+            Ok(res) => res,
+            Err(time_limit) => return Err(self.timeout_error(time_limit)),
+        };
+        let mut report = RunReport::from_tracing(&self._tracing);
+        report.run_id = self.run_id.clone();
+        report.first_error = self.first_error.clone();
+        report.deps = self
+            .rev_adjacency_list
+            .iter()
+            .filter(|(_, deps)| !deps.is_empty())
+            .map(|(name, deps)| (name.clone(), deps.clone()))
+            .collect();
+        for (name, outcome) in report.outcomes.iter_mut() {
+            if let Outcome::Failed(error) = outcome {
+                if self.non_critical.contains(name) {
+                    *outcome = Outcome::FailedNonCritical(std::mem::take(error));
+                }
+            }
+        }
+        let stats = self.run_stats();
+        let counts = self.run_counts();
+        tracing::info!(
+            run_id = %self.run_id,
+            status = if res.is_ok() { "done" } else { "failed" },
+            completed = counts.done,
+            failed = counts.failed,
+            skipped = counts.skipped,
+            wall_us = stats.wall_clock.as_micros() as u64,
+            "run finished"
+        );
+        self.log_summary();
+        res.map(|_| report)
+    }
+
+    // the human readable tracing of every exector, only with `set_log_summary`
+    fn log_summary(&self) {
+        if self.log_summary {
+            tracing::info!(run_id = %self.run_id, "exector tracing info: {}", self._tracing);
+        }
+    }
+
+    // tell a stuck exector from a time limit that is simply too short
+    fn timeout_error(&self, time_limit: Duration) -> RunError {
+        let report = RunReport::from_tracing(&self._tracing);
+        let (running, not_started) = (report.in_progress(), report.not_started());
+        let finished = self.run_counts().finished();
+        tracing::error!(
+            run_id = %self.run_id,
+            status = "timeout",
+            time_limit_ms = time_limit.as_millis() as u64,
+            running = ?running,
+            not_started = ?not_started,
+            finished,
+            "run timeout"
+        );
+        self.log_summary();
+        RunError::Timeout {
+            elapsed_ms: time_limit.as_millis() as u64,
+            in_progress: running,
+            not_started,
+            finished,
+            completed: report.completed_durations(),
+        }
+    }
+
+    // exectors downstream of the start nodes still waiting for deps once nothing is running.
+    // exectors only reachable from outside the entry nodes are left out on purpose
+    fn stalled_error(&self, start_exectors: &[FastStr], state: &RunState) -> Option<RunError> {
+        let mut reached: AHashSet<&FastStr> = start_exectors.iter().collect();
+        let mut stack: Vec<&FastStr> = start_exectors.iter().collect();
+        while let Some(name) = stack.pop() {
+            for next in self.dependents_of(name) {
+                if state.remaining_deps.contains_key(next) && reached.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        let mut stuck: Vec<(FastStr, Vec<FastStr>)> = reached
+            .into_iter()
+            .filter(|name| {
+                state.remaining_deps[*name] > 0 && !state.cancelled_exector_names.contains(*name)
+            })
+            .map(|name| {
+                let waiting_for = self
+                    .deps_of(name)
+                    .filter(|dep| !state.propagated_exector_names.contains(*dep))
+                    .cloned()
+                    .collect();
+                (name.clone(), waiting_for)
+            })
+            .collect();
+        if stuck.is_empty() {
+            return None;
+        }
+        stuck.sort_unstable();
+
+        tracing::error!(
+            run_id = %self.run_id,
+            status = "stalled",
+            stuck = ?stuck,
+            "run stalled with exectors waiting for deps"
+        );
+        self.log_summary();
+        Some(RunError::Stalled {
+            stuck,
+            completed: RunReport::from_tracing(&self._tracing).completed_durations(),
+        })
+    }
+
+    // the run time limit, 0 and u64::MAX mean no limit
+    fn time_limit(&self) -> Option<Duration> {
+        match self.timeout_ms {
+            0 | u64::MAX => None,
+            timeout_ms => Some(Duration::from_millis(timeout_ms)),
+        }
+    }
+
+    // drive `run` on a fresh current thread runtime, for callers without one.
+    // panics when called inside a tokio runtime, blocking there could deadlock it
+    #[cfg(feature = "blocking")]
+    pub fn run_blocking(&mut self) -> anyhow::Result<()> {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            panic!("run_blocking called inside a tokio runtime, use run().await instead");
+        }
+
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(self.run())
+            .map_err(Into::into)
+    }
+
+    async fn run_inner(&mut self, scope: Option<AHashSet<FastStr>>) -> Result<(), RunError> {
+        self.remove_scheduled_exectors();
+        let mut start_exectors = self.pre_check_and_find_start_nodes()?;
+        if let Some(scope) = &scope {
+            start_exectors = scope
+                .iter()
+                .filter(|name| !self.rev_adjacency_list.contains_key(*name))
+                .cloned()
+                .collect();
+            start_exectors.sort_unstable();
+        }
+        self._tracing.reset();
+        self.liveness.reset();
+        self.first_error = None;
+        self.context = Arc::new(Context::new());
+        let (scheduler, mut schedule_receiver) = scheduler::channel();
+        self.context.insert(scheduler);
+
+        let admission = Admission::new(
+            self.max_concurrency,
+            &self.group_limits,
+            &self.resource_budget,
+        );
+        let mut join_set = JoinSet::new();
+        let run_exectors: Vec<FastStr> = match &scope {
+            Some(scope) => scope.iter().cloned().collect(),
+            None => self.exectors.keys().cloned().collect(),
+        };
+        let mut state = RunState {
+            ready_queue: VecDeque::new(),
+            delayed_exector_names: Vec::new(),
+            remaining_deps: run_exectors
+                .into_iter()
+                .map(|name| {
+                    let deps = self.rev_adjacency_list.get(&name).map_or(0, Vec::len);
+                    (name, deps)
+                })
+                .collect(),
+            failed_exector_names: AHashSet::new(),
+            cached_exector_names: AHashSet::new(),
+            stopped_exector_names: AHashSet::new(),
+            propagated_exector_names: AHashSet::new(),
+            started_exector_names: AHashSet::new(),
+            cancelled_exector_names: AHashSet::new(),
+            abort_handles: AHashMap::new(),
+            inline_tasks: FuturesUnordered::new(),
+            running_tasks: AHashMap::new(),
+            last_tenant: None,
+        };
+
+        // a branch cancelled between runs is dropped before anything starts
+        let mut branch_cancels = FuturesUnordered::new();
+        let mut cancelled_targets = Vec::new();
+        for (target, token) in &self.branch_tokens {
+            if token.is_cancelled() {
+                cancelled_targets.push(target.clone());
+                continue;
+            }
+            let (target, cancelled) = (target.clone(), token.clone().cancelled_owned());
+            branch_cancels.push(async move {
+                cancelled.await;
+                target
+            });
+        }
+        for target in cancelled_targets {
+            self.cancel_branch(target, &mut state);
+        }
+
+        let mut skipped_exector_names = Vec::new();
+        for name in start_exectors.clone() {
+            if let Some(name) = self.enqueue_ready(name, &mut state) {
+                skipped_exector_names.push(name);
+            }
+        }
+        self.propagate_ready(skipped_exector_names, &mut state);
+        self.dispatch(&mut state, &admission, &mut join_set);
+
+        loop {
+            // backpressure of bounded channels with `OverflowPolicy::Block`
+            if let Some(progress_sender) = &self.progress_sender {
+                progress_sender.wait_for_room().await;
+            }
+            if let Some(results_sender) = &self.results_sender {
+                results_sender.wait_for_room().await;
+            }
+            // exectors held back while the progress channel was full
+            if !state.ready_queue.is_empty() {
+                self.dispatch(&mut state, &admission, &mut join_set);
+            }
+            if join_set.is_empty() && state.inline_tasks.is_empty() {
+                break;
+            }
+            // an exector schedules before it returns, so its requests are handled before its
+            // completion and a scheduled exector can depend on it
+            let completed = tokio::select! {
+                biased;
+                Some(request) = schedule_receiver.recv() => {
+                    let res = self.schedule_exector(request.exector, request.deps, &mut state);
+                    // the exector may have stopped waiting
+                    let _ = request.reply.send(res);
+                    self.dispatch(&mut state, &admission, &mut join_set);
+                    continue;
+                }
+                Some(target) = branch_cancels.next() => {
+                    self.cancel_branch(target, &mut state);
+                    self.dispatch(&mut state, &admission, &mut join_set);
+                    continue;
+                }
+                Some((name, res)) = state.inline_tasks.next() => res.map_err(|panic| (name, panic)),
+                Some(ready_handle) = join_set.join_next_with_id() => match ready_handle {
+                    Ok((id, node_result)) => {
+                        state.running_tasks.remove(&id);
+                        Ok(node_result)
+                    }
+                    Err(err) if err.is_panic() => {
+                        let name = state.running_tasks.remove(&err.id()).unwrap();
+                        Err((name, err.into_panic()))
+                    }
+                    // the target of a cancelled branch, marked when its result is handled
+                    Err(err) if err.is_cancelled() => {
+                        let name = state.running_tasks.remove(&err.id()).unwrap();
+                        Ok(NodeResult {
+                            name,
+                            attempts: 0,
+                            history: Vec::new(),
+                            res: Ok(()),
+                            finish: Finish::Ran,
+                        })
+                    }
+                    Err(err) => panic!("join handle error: {:?}", err),
+                },
+            };
+            let NodeResult {
+                name: ready_exector_name,
+                attempts,
+                history,
+                res,
+                finish,
+            } = match completed {
+                // a delayed exector of the branch was marked already
+                Ok(node_result) if state.cancelled_exector_names.contains(&node_result.name) => {
+                    if node_result.finish != Finish::Delayed {
+                        self.liveness.completed(self._tracing.clock().now_micros());
+                        self._tracing.cancel(&node_result.name, true);
+                        self.notify_node_cancelled(&node_result.name, true);
+                    }
+                    continue;
+                }
+                Ok(node_result) if node_result.finish == Finish::Delayed => {
+                    state.ready_queue.push_back(node_result.name);
+                    self.dispatch(&mut state, &admission, &mut join_set);
+                    continue;
+                }
+                Ok(node_result) => {
+                    self.liveness.completed(self._tracing.clock().now_micros());
+                    node_result
+                }
+                // a panicked exector fails its node like a returned error
+                Err((name, panic)) => {
+                    self.liveness.completed(self._tracing.clock().now_micros());
+                    let message = panic_message(panic);
+                    NodeResult {
+                        res: Err(format!("exector {} panicked: {}", name, message).into()),
+                        name,
+                        attempts: 1,
+                        history: Vec::new(),
+                        finish: Finish::Ran,
+                    }
+                }
+            };
+            self._tracing.record_history(&ready_exector_name, history);
+            let non_critical_error = match &res {
+                Err(err) if self.non_critical.contains(&ready_exector_name) => {
+                    Some(error_message(&**err))
+                }
+                _ => None,
+            };
+            if let Some(error) = non_critical_error {
+                // recorded like any failure, but the run goes on as if it completed
+                self._tracing
+                    .fail(&ready_exector_name, attempts, error.clone());
+                self.notify_node_complete(&ready_exector_name, attempts, Some(error), None);
+                self.cache_keys.remove(&ready_exector_name);
+            } else if let Err(err) = res {
+                let error = error_message(&*err);
+                if self.first_error.is_none() {
+                    self.first_error = Some((ready_exector_name.clone(), error.clone()));
+                }
+                self._tracing
+                    .fail(&ready_exector_name, attempts, error.clone());
+                self.notify_node_complete(&ready_exector_name, attempts, Some(error), None);
+                state
+                    .failed_exector_names
+                    .insert(ready_exector_name.clone());
+                self.cache_keys.remove(&ready_exector_name);
+
+                if self.failure_mode == FailureMode::StopOnError {
+                    join_set.abort_all();
+                    return Err(RunError::NodeFailed {
+                        name: ready_exector_name,
+                        source: err,
+                        completed: RunReport::from_tracing(&self._tracing).completed_durations(),
+                    });
+                }
+            } else if finish == Finish::ShortCircuited {
+                // counts as a cache hit, so next exectors may keep their cached results too
+                self._tracing.short_circuit(&ready_exector_name, attempts);
+                state
+                    .cached_exector_names
+                    .insert(ready_exector_name.clone());
+                self.notify_node_complete(
+                    &ready_exector_name,
+                    attempts,
+                    None,
+                    Some("short_circuit"),
+                );
+            } else if finish == Finish::ConditionFalse {
+                // like a false sync condition its next exectors treat it as satisfied
+                self._tracing.skip_started(&ready_exector_name, attempts);
+                self.notify_node_complete(&ready_exector_name, attempts, None, Some("condition"));
+            } else {
+                self._tracing.done(&ready_exector_name, attempts);
+                self.notify_node_complete(&ready_exector_name, attempts, None, None);
+                if let Some(cache_key) = self.exectors[&ready_exector_name].cache_key() {
+                    self.cache_keys
+                        .insert(ready_exector_name.clone(), cache_key);
+                }
+            }
+
+            if finish == Finish::StoppedBranch {
+                tracing::info!(
+                    run_id = %self.run_id,
+                    node = %ready_exector_name,
+                    "exector stopped its branch"
+                );
+                state
+                    .stopped_exector_names
+                    .insert(ready_exector_name.clone());
+            }
+
+            self.propagate_ready(vec![ready_exector_name], &mut state);
+            self.dispatch(&mut state, &admission, &mut join_set);
+        }
+
+        match self.stalled_error(&start_exectors, &state) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    // drop the branch of a target whose token was cancelled, see `branch_token`
+    fn cancel_branch(&mut self, target: FastStr, state: &mut RunState) {
+        // not part of this run, finished or already cancelled
+        if !state.remaining_deps.contains_key(&target)
+            || state.propagated_exector_names.contains(&target)
+            || state.cancelled_exector_names.contains(&target)
+        {
+            return;
+        }
+
+        // the target and everything below it, none of which started
+        let mut branch: AHashSet<FastStr> = AHashSet::new();
+        let mut stack = vec![target.clone()];
+        while let Some(name) = stack.pop() {
+            if branch.insert(name.clone()) {
+                stack.extend(
+                    self.dependents_of(&name)
+                        .filter(|next| state.remaining_deps.contains_key(*next))
+                        .cloned(),
+                );
+            }
+        }
+        // then the ancestors left with no next exector outside the branch, a candidate is
+        // checked again each time another of its next exectors joins
+        let mut candidates: Vec<FastStr> = branch
+            .iter()
+            .flat_map(|name| self.deps_of(name))
+            .cloned()
+            .collect();
+        while let Some(name) = candidates.pop() {
+            if branch.contains(&name)
+                || !state.remaining_deps.contains_key(&name)
+                || state.started_exector_names.contains(&name)
+                || state.propagated_exector_names.contains(&name)
+            {
+                continue;
+            }
+            let exclusive = self
+                .dependents_of(&name)
+                .filter(|next| state.remaining_deps.contains_key(*next))
+                .all(|next| branch.contains(next));
+            if exclusive {
+                candidates.extend(self.deps_of(&name).cloned());
+                branch.insert(name);
+            }
+        }
+
+        let mut branch: Vec<FastStr> = branch.into_iter().collect();
+        branch.sort_unstable();
+        tracing::info!(
+            run_id = %self.run_id,
+            target = %target,
+            branch = ?branch,
+            "branch cancelled"
+        );
+        state.ready_queue.retain(|name| !branch.contains(name));
+        state
+            .delayed_exector_names
+            .retain(|name| !branch.contains(name));
+        for name in branch {
+            state.cancelled_exector_names.insert(name.clone());
+            // only the target can be running, it is marked once its task returns
+            if state.started_exector_names.contains(&name) {
+                if let Some(handle) = state.abort_handles.get(&name) {
+                    handle.abort();
+                }
+                continue;
+            }
+            self._tracing.cancel(&name, false);
+            self.notify_node_cancelled(&name, false);
+        }
+    }
+
+    // add an exector sent through the `Scheduler` to the graph of the running run
+    fn schedule_exector(
+        &mut self,
+        exector: Arc<dyn Executor>,
+        mut deps: Vec<FastStr>,
+        state: &mut RunState,
+    ) -> anyhow::Result<()> {
+        let name = exector.name();
+        deps.extend(exector.deps());
+        for dep in &deps {
+            if !state.remaining_deps.contains_key(dep) {
+                return Err(anyhow::anyhow!(
+                    "scheduled exector {} depends on {}, which is not part of the run",
+                    name,
+                    dep
+                ));
+            }
+            // its completion is gone, the new exector would wait for it forever
+            if state.propagated_exector_names.contains(dep) {
+                return Err(anyhow::anyhow!(
+                    "scheduled exector {} depends on {}, which already completed",
+                    name,
+                    dep
+                ));
+            }
+            if state.cancelled_exector_names.contains(dep) {
+                return Err(anyhow::anyhow!(
+                    "scheduled exector {} depends on {}, which was cancelled",
+                    name,
+                    dep
+                ));
+            }
+        }
+        // checked before anything is added, declared deps are part of `deps`
+        let edges: Vec<_> = deps.iter().map(|dep| (dep.clone(), name.clone())).collect();
+        self.check_limits(&edges)?;
+        self.try_add_shared_exector(exector)?;
+        if let Err(err) = self.try_add_deps(name.clone(), deps) {
+            self.remove_executor(&name);
+            return Err(err);
+        }
+        tracing::info!(run_id = %self.run_id, node = %name, "exector scheduled");
+        self.scheduled_exectors.push(name.clone());
+
+        let remaining_deps = self.rev_adjacency_list.get(&name).map_or(0, Vec::len);
+        state.remaining_deps.insert(name.clone(), remaining_deps);
+        if remaining_deps == 0 {
+            if let Some(skipped) = self.enqueue_ready(name, state) {
+                self.propagate_ready(vec![skipped], state);
+            }
+        }
+        Ok(())
+    }
+
+    // a node skipped after it started is reported with a zero duration
+    fn notify_node_complete(
+        &self,
+        name: &FastStr,
+        attempts: u32,
+        error: Option<String>,
+        skip_reason: Option<&'static str>,
+    ) {
+        let duration = self
+            .node_timing(name)
+            .ok()
+            .and_then(|timing| timing.duration())
+            .unwrap_or_default();
+        let dur_us = duration.as_micros() as u64;
+        match &error {
+            Some(error) => tracing::error!(
+                run_id = %self.run_id,
+                node = %name,
+                status = "failed",
+                attempts,
+                dur_us,
+                error = %error,
+                "exector failed"
+            ),
+            None if skip_reason.is_some() => tracing::info!(
+                run_id = %self.run_id,
+                node = %name,
+                status = "skipped",
+                reason = skip_reason,
+                attempts,
+                "exector skipped"
+            ),
+            None => {
+                tracing::info!(
+                    run_id = %self.run_id,
+                    node = %name,
+                    status = "done",
+                    attempts,
+                    dur_us,
+                    "exector done"
+                )
+            }
+        }
+
+        let event = match &error {
+            Some(error) => ProgressEvent::Failed(name.clone(), error.clone()),
+            None => ProgressEvent::Finished(name.clone(), duration),
+        };
+        let outcome = NodeOutcome {
+            duration,
+            error,
+            cancelled: false,
+        };
+        self.send_node_outcome(name, event, outcome);
+    }
+
+    // a started target ran until it was aborted
+    fn notify_node_cancelled(&self, name: &FastStr, started: bool) {
+        let duration = match self.node_timing(name) {
+            Ok(timing) if started => {
+                Duration::from_micros((timing.end_micros - timing.start_micros).max(0) as u64)
+            }
+            _ => Duration::ZERO,
+        };
+        tracing::info!(
+            run_id = %self.run_id,
+            node = %name,
+            status = "cancelled",
+            started,
+            dur_us = duration.as_micros() as u64,
+            "exector cancelled"
+        );
+
+        let outcome = NodeOutcome {
+            duration,
+            error: None,
+            cancelled: true,
+        };
+        self.send_node_outcome(name, ProgressEvent::Cancelled(name.clone()), outcome);
+    }
+
+    fn send_node_outcome(&self, name: &FastStr, event: ProgressEvent, outcome: NodeOutcome) {
+        if let Some(progress_sender) = &self.progress_sender {
+            progress_sender.send(event);
+        }
+        if let Some(results_sender) = &self.results_sender {
+            results_sender.send((name.clone(), outcome.clone()));
+        }
+        if let Some(hook) = &self.node_complete_hook {
+            hook(name, outcome);
+        }
+    }
+
+    // walk down from exectors that just became ready, an exector is ready once its count of
+    // remaining deps drops to zero. a skipped exector is ready at once, so its next exectors
+    // are checked in the same round
+    fn propagate_ready(&mut self, mut ready_exector_names: Vec<FastStr>, state: &mut RunState) {
+        while let Some(ready_exector_name) = ready_exector_names.pop() {
+            state
+                .propagated_exector_names
+                .insert(ready_exector_name.clone());
+            let mut next_ready_exector_names = Vec::new();
+            for next_exector_name in self
+                .adjacency_list
+                .get(&ready_exector_name)
+                .into_iter()
+                .flatten()
+            {
+                // not part of this run
+                let Some(remaining_deps) = state.remaining_deps.get_mut(next_exector_name) else {
+                    continue;
+                };
+                *remaining_deps -= 1;
+                if *remaining_deps == 0 {
+                    next_ready_exector_names.push(next_exector_name.clone());
+                }
+            }
+
+            for next_exector_name in next_ready_exector_names {
+                // dropped with a cancelled branch, whatever its deps did
+                if state.cancelled_exector_names.contains(&next_exector_name) {
+                    continue;
+                }
+                if self.rev_adjacency_list[&next_exector_name]
+                    .iter()
+                    .any(|dep| {
+                        state.failed_exector_names.contains(dep)
+                            && self.dep_kind(&next_exector_name, dep) == DepKind::OnSuccess
+                    })
+                {
+                    tracing::warn!(
+                        run_id = %self.run_id,
+                        node = %next_exector_name,
+                        status = "skipped",
+                        reason = "dep_failed",
+                        "exector skipped"
+                    );
+                    self._tracing.skip(&next_exector_name);
+                    state.failed_exector_names.insert(next_exector_name.clone());
+                    ready_exector_names.push(next_exector_name);
+                    continue;
+                }
+
+                // only reached through stopped branches
+                if !state.stopped_exector_names.is_empty()
+                    && self.rev_adjacency_list[&next_exector_name]
+                        .iter()
+                        .all(|dep| state.stopped_exector_names.contains(dep))
+                {
+                    tracing::info!(
+                        run_id = %self.run_id,
+                        node = %next_exector_name,
+                        status = "skipped",
+                        reason = "branch_stopped",
+                        "exector skipped"
+                    );
+                    self._tracing.skip(&next_exector_name);
+                    state
+                        .stopped_exector_names
+                        .insert(next_exector_name.clone());
+                    ready_exector_names.push(next_exector_name);
+                    continue;
+                }
+
+                if let Some(name) = self.enqueue_ready(next_exector_name, state) {
+                    ready_exector_names.push(name);
+                }
+            }
+        }
+    }
+
+    fn dep_kind(&self, name: &FastStr, dep: &FastStr) -> DepKind {
+        match self.dep_kinds.get(name).and_then(|kinds| kinds.get(dep)) {
+            Some(kind) => *kind,
+            None if self.failure_mode == FailureMode::SkipOnDepFailure => DepKind::OnSuccess,
+            None => DepKind::OnComplete,
+        }
+    }
+
+    // queue an exector whose deps are all done, unless its condition is false or its cached
+    // result is still valid. a skipped exector is given back so its next exectors are checked
+    fn enqueue_ready(&mut self, name: FastStr, state: &mut RunState) -> Option<FastStr> {
+        if state.cancelled_exector_names.contains(&name) {
+            return None;
+        }
+        if !self.condition_met(&name) {
+            return Some(name);
+        }
+        if self.cache_hit(&name, state) {
+            tracing::info!(
+                run_id = %self.run_id,
+                node = %name,
+                status = "skipped",
+                reason = "cached",
+                "exector skipped"
+            );
+            self._tracing.skip_cached(&name);
+            state.cached_exector_names.insert(name.clone());
+            return Some(name);
+        }
+
+        self._tracing.ready(&name);
+        if self.start_delays.contains_key(&name) {
+            state.delayed_exector_names.push(name);
+        } else {
+            state.ready_queue.push_back(name);
+        }
+        None
+    }
+
+    // a dep that ran again may have changed the inputs, so only deps skipped as cached keep it
+    fn cache_hit(&self, name: &FastStr, state: &RunState) -> bool {
+        let Some(cache_key) = self.exectors[name].cache_key() else {
+            return false;
+        };
+        self.cache_keys.get(name) == Some(&cache_key)
+            && self
+                .rev_adjacency_list
+                .get(name)
+                .into_iter()
+                .flatten()
+                .all(|dep| state.cached_exector_names.contains(dep))
+    }
+
+    // an exector whose condition is false is skipped, unlike a failed dep it counts as
+    // satisfied for its next exectors
+    fn condition_met(&mut self, name: &FastStr) -> bool {
+        let met = self
+            .conditions
+            .get(name)
+            .is_none_or(|condition| condition(&self.context));
+        if !met {
+            tracing::info!(
+                run_id = %self.run_id,
+                node = %name,
+                status = "skipped",
+                reason = "condition",
+                "exector skipped"
+            );
+            self._tracing.skip(name);
+        }
+        met
+    }
+
+    fn spawn(
+        &self,
+        join_set: &mut JoinSet<NodeResult>,
+        task: impl Future<Output = NodeResult> + Send + 'static,
+    ) -> AbortHandle {
+        match &self.runtime {
+            Some(runtime) => join_set.spawn_on(task, runtime),
+            None => join_set.spawn(task),
+        }
+    }
+
+    // spawn ready exectors in order while the concurrency limits allow, the rest wait in the queue
+    fn dispatch(
+        &mut self,
+        state: &mut RunState,
+        admission: &Admission,
+        join_set: &mut JoinSet<NodeResult>,
+    ) {
+        // the delay runs as a task of its own that holds no concurrency slot
+        for name in state.delayed_exector_names.drain(..) {
+            let delay = self.start_delays[&name];
+            let delayed_name = name.clone();
+            let handle = self.spawn(join_set, async move {
+                tokio::time::sleep(delay).await;
+                NodeResult {
+                    name: delayed_name,
+                    attempts: 0,
+                    history: Vec::new(),
+                    res: Ok(()),
+                    finish: Finish::Delayed,
+                }
+            });
+            state.running_tasks.insert(handle.id(), name);
+        }
+        if !self.priorities.is_empty() {
+            state
+                .ready_queue
+                .make_contiguous()
+                .sort_by_key(|name| Reverse(self.priorities.get(name).copied().unwrap_or(0)));
+        }
+        if !self.hints.is_empty() {
+            self.apply_hints(&mut state.ready_queue);
+        }
+        if !self.tenants.is_empty() {
+            self.interleave_tenants(state);
+        }
+
+        let ready_queue = &mut state.ready_queue;
+
+        let mut index = 0;
+        while index < ready_queue.len() {
+            // no `Started` event past the capacity of a blocking channel
+            if let Some(progress_sender) = &self.progress_sender {
+                if !progress_sender.has_room() {
+                    break;
+                }
+            }
+            let group = self.concurrency_groups.get(&ready_queue[index]);
+            let cost = self.costs.get(&ready_queue[index]);
+            let permits = match admission.try_acquire(group, cost) {
+                Ok(permits) => permits,
+                Err(AdmissionError::Global) => break,
+                Err(AdmissionError::Group | AdmissionError::Resources) => {
+                    index += 1;
+                    continue;
+                }
+            };
+
+            let exector_name = ready_queue.remove(index).unwrap();
+            let exector = self.exectors[&exector_name].clone();
+            self._tracing.start(&exector_name);
+            if let Some(progress_sender) = &self.progress_sender {
+                progress_sender.send(ProgressEvent::Started(exector_name.clone()));
+            }
+            if !self.tenants.is_empty() {
+                state.last_tenant = Some(self.tenant_of(&exector_name));
+            }
+            let task = self.build_task(exector, permits);
+            self.liveness.spawned();
+            state.started_exector_names.insert(exector_name.clone());
+            if self.inline_exectors.contains(&exector_name) {
+                state.inline_tasks.push(Box::pin(async move {
+                    (exector_name, AssertUnwindSafe(task).catch_unwind().await)
+                }));
+            } else {
+                let handle = self.spawn(join_set, task);
+                state
+                    .running_tasks
+                    .insert(handle.id(), exector_name.clone());
+                state.abort_handles.insert(exector_name, handle);
+            }
+        }
+    }
+
+    // move every exector after the ready exectors of the same priority it is hinted to follow,
+    // when hints form a cycle the first exector of it goes first
+    fn apply_hints(&self, ready_queue: &mut VecDeque<FastStr>) {
+        let priority = |name: &FastStr| self.priorities.get(name).copied().unwrap_or(0);
+        let mut pending: Vec<FastStr> = ready_queue.drain(..).collect();
+        while !pending.is_empty() {
+            let index = pending
+                .iter()
+                .position(|name| {
+                    !self.hints.get(name).into_iter().flatten().any(|before| {
+                        pending.contains(before) && priority(before) == priority(name)
+                    })
+                })
+                .unwrap_or(0);
+            ready_queue.push_back(pending.remove(index));
+        }
+    }
+
+    // round robin over the tenants starting after the tenant served last, keeping the order
+    // of the ready exectors within each tenant
+    fn interleave_tenants(&self, state: &mut RunState) {
+        let mut tenant_queues: BTreeMap<FastStr, VecDeque<FastStr>> = BTreeMap::new();
+        for name in state.ready_queue.drain(..) {
+            tenant_queues
+                .entry(self.tenant_of(&name))
+                .or_default()
+                .push_back(name);
+        }
+        let mut tenant_queues: Vec<_> = tenant_queues.into_values().collect();
+        if let Some(last_tenant) = &state.last_tenant {
+            let first = tenant_queues
+                .iter()
+                .position(|queue| self.tenant_of(&queue[0]) > *last_tenant)
+                .unwrap_or(0);
+            tenant_queues.rotate_left(first);
+        }
+
+        while !tenant_queues.is_empty() {
+            for queue in &mut tenant_queues {
+                state.ready_queue.extend(queue.pop_front());
+            }
+            tenant_queues.retain(|queue| !queue.is_empty());
+        }
+    }
+
+    fn tenant_of(&self, name: &FastStr) -> FastStr {
+        self.tenants.get(name).cloned().unwrap_or_default()
+    }
+
+    // check the graph without running it: start nodes, cycles and exectors that can never be scheduled
+    pub fn validate(&self) -> Result<(), RunError> {
+        let start_nodes = self.pre_check_and_find_start_nodes()?;
+        let reachable = self.find_reachable_nodes(&start_nodes);
+
+        let mut unreachable_nodes: Vec<_> = self
+            .exectors
+            .keys()
+            .filter(|name| !reachable.contains(*name))
+            .cloned()
+            .collect();
+        if !unreachable_nodes.is_empty() {
+            unreachable_nodes.sort_unstable();
+            return Err(RunError::Validation(format!(
+                "unreachable exectors: {:?}, please check their deps",
+                unreachable_nodes
+            )));
+        }
+
+        Ok(())
+    }
+
+    // the exectors a run starts with, sorted: the entry nodes when set, else every exector
+    // without deps. empty means a run fails before anything starts
+    pub fn ready_at_start(&self) -> Vec<FastStr> {
+        let mut start_nodes = match &self.entry_nodes {
+            Some(entry_nodes) => entry_nodes.clone(),
+            None => self.find_start_nodes(),
+        };
+        start_nodes.sort_unstable();
+        start_nodes.dedup();
+        start_nodes
+    }
+
+    // the deps of the exector that had not finished when the latest run ended, sorted, e.g.
+    // to see what an exector left not started was waiting for. needs tracing
+    pub fn unsatisfied_deps(&self, name: &str) -> anyhow::Result<Vec<FastStr>> {
+        if !self.exectors.contains_key(name) {
+            return Err(anyhow::anyhow!("unknown exector {}", name));
+        }
+        let mut unsatisfied = Vec::new();
+        for dep in self.deps_of(name) {
+            let status = self._tracing.get_tracing_info(dep)?.status;
+            if matches!(
+                status,
+                Status::NotStarted | Status::Doing | Status::Cancelled
+            ) {
+                unsatisfied.push(dep.clone());
+            }
+        }
+        unsatisfied.sort_unstable();
+        Ok(unsatisfied)
+    }
+
+    fn check_dangling_edges(&self) -> Result<(), RunError> {
+        let mut dangling_edges: Vec<_> = self
+            .adjacency_list
+            .iter()
+            .flat_map(|(from, to_list)| to_list.iter().map(move |to| (from, to)))
+            .filter(|(from, to)| {
+                !self.exectors.contains_key(*from) || !self.exectors.contains_key(*to)
+            })
+            .collect();
+        if !dangling_edges.is_empty() {
+            dangling_edges.sort_unstable();
+            return Err(RunError::Validation(format!(
+                "edges reference unknown exectors: {}",
+                dangling_edges
+                    .iter()
+                    .map(|(from, to)| format!("{} -> {}", from, to))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn check_self_loops(&self) -> Result<(), RunError> {
+        let mut self_loops: Vec<_> = self
+            .adjacency_list
+            .iter()
+            .filter(|(from, to_list)| to_list.contains(from))
+            .map(|(from, _)| from)
+            .collect();
+        self_loops.sort_unstable();
+        match self_loops.first() {
+            Some(name) => Err(RunError::Cycle(vec![(*name).clone(), (*name).clone()])),
+            None => Ok(()),
+        }
+    }
+
+    // same readiness rule as run_inner, a node is reachable once all its deps are reachable
+    fn find_reachable_nodes(&self, start_nodes: &[FastStr]) -> AHashSet<FastStr> {
+        let mut reachable: AHashSet<_> = start_nodes.iter().cloned().collect();
+        let mut stack = start_nodes.to_vec();
+        while let Some(node) = stack.pop() {
+            let Some(next_nodes) = self.adjacency_list.get(&node) else {
+                continue;
+            };
+
+            for next_node in next_nodes {
+                if reachable.contains(next_node) || !self.exectors.contains_key(next_node) {
+                    continue;
+                }
+                if self.rev_adjacency_list[next_node]
+                    .iter()
+                    .all(|dep| reachable.contains(dep))
+                {
+                    reachable.insert(next_node.clone());
+                    stack.push(next_node.clone());
+                }
+            }
+        }
+
+        reachable
+    }
+
+    fn pre_check_and_find_start_nodes(&self) -> Result<Vec<FastStr>, RunError> {
+        if self.exectors.is_empty() {
+            return Err(RunError::EmptyGraph);
+        }
+        // scheduling looks exectors up by edge endpoints, so they must all exist
+        self.check_dangling_edges()?;
+        // the shortest cycle, named before a longer one through the same node
+        self.check_self_loops()?;
+
+        let start_nodes = match &self.entry_nodes {
+            Some(entry_nodes) => self.check_entry_nodes(entry_nodes)?,
+            None => self.find_start_nodes(),
+        };
+        // before the start node check, a graph where every exector has deps holds a cycle
+        self.check_cycle()?;
+        if start_nodes.is_empty() {
+            return Err(RunError::NoStartNodes);
+        }
+
+        Ok(start_nodes)
+    }
+
+    // three color dfs from every node, so cycles unreachable from the start nodes are found too
+    fn check_cycle(&self) -> Result<(), RunError> {
+        #[derive(PartialEq)]
+        enum Color {
+            // on the dfs stack
+            Gray,
+            // every node after it is checked
+            Black,
+        }
+
+        let mut colors = AHashMap::new();
+        let mut roots: Vec<_> = self.adjacency_list.keys().collect();
+        roots.sort_unstable();
+        for root in roots {
+            if colors.contains_key(root) {
+                continue;
+            }
+
+            colors.insert(root, Color::Gray);
+            // node and the index of the next neighbor to visit
+            let mut stack = vec![(root, 0)];
+            while let Some((node, index)) = stack.last_mut() {
+                let Some(neighbor) = self
+                    .adjacency_list
+                    .get(*node)
+                    .and_then(|neighbors| neighbors.get(*index))
+                else {
+                    colors.insert(*node, Color::Black);
+                    stack.pop();
+                    continue;
+                };
+                *index += 1;
+
+                match colors.get(neighbor) {
+                    None => {
+                        colors.insert(neighbor, Color::Gray);
+                        stack.push((neighbor, 0));
+                    }
+                    Some(Color::Gray) => {
+                        let mut path: Vec<_> = stack
+                            .iter()
+                            .map(|(node, _)| (*node).clone())
+                            .skip_while(|node| node != neighbor)
+                            .collect();
+                        path.push(neighbor.clone());
+                        return Err(RunError::Cycle(path));
+                    }
+                    Some(Color::Black) => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_entry_nodes(&self, entry_nodes: &[FastStr]) -> Result<Vec<FastStr>, RunError> {
+        for entry_node in entry_nodes {
+            if !self.exectors.contains_key(entry_node) {
+                return Err(RunError::Validation(format!(
+                    "entry node {} is not an exector",
+                    entry_node
+                )));
+            }
+            if let Some(deps) = self.rev_adjacency_list.get(entry_node) {
+                return Err(RunError::Validation(format!(
+                    "entry node {} has deps: {:?}",
+                    entry_node, deps
+                )));
+            }
+        }
+
+        Ok(entry_nodes.to_vec())
+    }
+
+    // sorted, so runs of equal graphs start in the same order
+    fn find_start_nodes(&self) -> Vec<FastStr> {
+        let mut start_nodes = Vec::new();
+        for (name, _) in self.exectors.iter() {
+            if !self.rev_adjacency_list.contains_key(name) {
+                start_nodes.push(name.clone());
+            }
+        }
+        start_nodes.sort_unstable();
+
+        start_nodes
+    }
+
+    fn build_task(
+        &self,
+        exector: Arc<dyn Executor>,
+        permits: Vec<OwnedSemaphorePermit>,
+    ) -> impl Future<Output = NodeResult> + Send + 'static {
+        let name = exector.name();
+        let node_timeout_ms = self
+            .node_timeouts
+            .get(&name)
+            .or_else(|| self.budgeted_timeouts.get(&name))
+            .copied();
+        let retry_policy = self.retry_policies.get(&name).copied();
+        let shared_ctx = self.context.clone();
+        let ctx = match self.context_scopes.get(&name) {
+            Some(scope) => Arc::new(scope(&shared_ctx)),
+            None => shared_ctx.clone(),
+        };
+        let scoped_ctx = ctx.clone();
+        let scope_snapshot =
+            (!Arc::ptr_eq(&scoped_ctx, &shared_ctx)).then(|| scoped_ctx.snapshot());
+        let clock = self._tracing.clock();
+        let exector = match self.once_registries.get(&name) {
+            Some(registry) => once::run_once(exector, registry),
+            None => exector,
+        };
+        let exector = match &self.progress_sender {
+            Some(progress_sender) => progress::with_progress(exector, progress_sender.clone()),
+            None => exector,
+        };
+        // with no middlerware the exector is always called
+        let (exector, reached) = if self.middlerwares.is_empty() {
+            (exector, None)
+        } else {
+            let (exector, reached) = middlerware::track_reached(exector);
+            (exector, Some(reached))
+        };
+        let exector = middlerware::chain(exector, &self.middlerwares, &ctx);
+        #[cfg(feature = "prometheus")]
+        let metrics = self.metrics.clone();
+
+        let blocking = self.blocking_exectors.contains(&name);
+        let run_id = self.run_id.clone();
+        let async_condition = self.async_conditions.get(&name).cloned();
+        let condition_ctx = ctx.clone();
+        let condition_error_mode = self.condition_error_mode;
+
+        // every attempt needs a fresh future, so keep the way to build one instead of the future itself
+        let build_exector_future = move || -> BoxExectorFuture {
+            let exector = exector.clone();
+            let ctx = ctx.clone();
+            if !blocking {
+                return Box::pin(async move { exector.execute(&ctx).await });
+            }
+
+            let handle = Handle::current();
+            let task = task::spawn_blocking(move || handle.block_on(exector.execute(&ctx)));
+            Box::pin(async move {
+                // a panic goes on to the run task, which fails the node with it
+                task.await
+                    .unwrap_or_else(|err| panic::resume_unwind(err.into_panic()))
+            })
+        };
+
+        let span = if self.span_enabled {
+            tracing::info_span!(
+                "executor",
+                name = %name,
+                start_us = tracing::field::Empty,
+                end_us = tracing::field::Empty,
+                attempts = tracing::field::Empty,
+                error = tracing::field::Empty,
+            )
+        } else {
+            Span::none()
+        };
+
+        let exector_span = span.clone();
+        let exector_future = async move {
+            let span = exector_span;
+            span.record("start_us", clock.now_micros());
+            #[cfg(feature = "prometheus")]
+            let (_in_flight, started) = (
+                metrics.as_ref().map(Metrics::start),
+                std::time::Instant::now(),
+            );
+            let max_attempts = retry_policy.map_or(1, |policy| policy.max_attempts.max(1));
+            let mut attempts = 0;
+            let mut history = Vec::new();
+            let condition = match &async_condition {
+                Some(condition) => condition(condition_ctx).await,
+                None => Ok(true),
+            };
+            let (res, finish) = match condition {
+                Ok(true) => {
+                    let res = loop {
+                        attempts += 1;
+                        if let Some(reached) = &reached {
+                            reached.store(false, Ordering::SeqCst);
+                        }
+                        let start_micros = clock.now_micros();
+                        let res =
+                            execute_with_timeout(&name, build_exector_future(), node_timeout_ms)
+                                .await;
+                        history.push(AttemptInfo {
+                            start_micros,
+                            end_micros: clock.now_micros(),
+                            error: res.as_ref().err().map(|err| error_message(&**err)),
+                        });
+                        match (res, retry_policy) {
+                            (Err(err), Some(policy))
+                                if attempts < max_attempts
+                                    && !err.is::<MiddlerwareSetupError>()
+                                    && !err.is::<NodeControl>() =>
+                            {
+                                let delay = policy.backoff.delay(attempts);
+                                tracing::warn!(
+                                    run_id = %run_id,
+                                    node = %name,
+                                    attempt = attempts,
+                                    error = %error_message(&*err),
+                                    retry_after_ms = delay.as_millis() as u64,
+                                    "exector attempt failed, retrying"
+                                );
+                                tokio::time::sleep(delay).await;
+                            }
+                            (res, _) => break res,
+                        }
+                    };
+                    match res {
+                        Err(err) if err.is::<NodeControl>() => (Ok(()), Finish::StoppedBranch),
+                        Ok(())
+                            if reached
+                                .as_ref()
+                                .is_some_and(|reached| !reached.load(Ordering::SeqCst)) =>
+                        {
+                            (Ok(()), Finish::ShortCircuited)
+                        }
+                        res => (res, Finish::Ran),
+                    }
+                }
+                Ok(false) => (Ok(()), Finish::ConditionFalse),
+                Err(err) => {
+                    let error = format!(
+                        "condition of exector {} failed: {}",
+                        name,
+                        error_message(&*err)
+                    );
+                    match condition_error_mode {
+                        ConditionErrorMode::Skip => {
+                            tracing::warn!(
+                                run_id = %run_id,
+                                node = %name,
+                                error = %error,
+                                "condition failed, skipping"
+                            );
+                            (Ok(()), Finish::ConditionFalse)
+                        }
+                        ConditionErrorMode::Fail => (Err(error.into()), Finish::Ran),
+                    }
+                }
+            };
+            if let Some(scope_snapshot) = &scope_snapshot {
+                scoped_ctx.write_back(scope_snapshot, &shared_ctx);
+            }
+
+            // release the concurrency slots before the scheduler sees the result
+            drop(permits);
+
+            span.record("end_us", clock.now_micros());
+            span.record("attempts", attempts);
+            if let Err(err) = &res {
+                span.record("error", tracing::field::debug(err));
+            }
+            #[cfg(feature = "prometheus")]
+            if let Some(metrics) = &metrics {
+                metrics.finish(&name, started.elapsed(), res.is_err());
+            }
+
+            NodeResult {
+                name,
+                attempts,
+                history,
+                res,
+                finish,
+            }
+        };
+
+        exector_future.instrument(span)
+    }
+}
+
+impl Drop for Manager {
+    fn drop(&mut self) {
+        if self.warn_unrun_drop && !self.ran && !self.exectors.is_empty() {
+            tracing::warn!(
+                exectors = self.exectors.len(),
+                "manager dropped without running its exectors"
+            );
+        }
+    }
+}
+
+// an inline exector with its name, a panic is caught like the join set does for tasks
+type InlineTask = Pin<Box<dyn Future<Output = (FastStr, thread::Result<NodeResult>)> + Send>>;
+
+// scheduling state of one run
+struct RunState {
+    // ready exectors waiting for a concurrency slot
+    ready_queue: VecDeque<FastStr>,
+    // ready exectors whose start delay is yet to be spawned
+    delayed_exector_names: Vec<FastStr>,
+    // deps each exector still waits for, exectors outside the run are absent
+    remaining_deps: AHashMap<FastStr, usize>,
+    // failed exectors and the exectors skipped because of them
+    failed_exector_names: AHashSet<FastStr>,
+    // exectors skipped with the result of a previous run
+    cached_exector_names: AHashSet<FastStr>,
+    // exectors that stopped their branch and the exectors pruned with them
+    stopped_exector_names: AHashSet<FastStr>,
+    // exectors whose next exectors no longer wait for them
+    propagated_exector_names: AHashSet<FastStr>,
+    // exectors handed to a task or the run loop
+    started_exector_names: AHashSet<FastStr>,
+    // exectors dropped with a cancelled branch
+    cancelled_exector_names: AHashSet<FastStr>,
+    // spawned exectors by name, to abort the target of a cancelled branch
+    abort_handles: AHashMap<FastStr, AbortHandle>,
+    // exectors set inline, polled by the run loop
+    inline_tasks: FuturesUnordered<InlineTask>,
+    // spawned exectors by task id, a panicked task only tells its id
+    running_tasks: AHashMap<task::Id, FastStr>,
+    // tenant of the exector spawned last, the next turn goes to the tenant after it
+    last_tenant: Option<FastStr>,
+}
+
+// marks the manager as running, cleared on drop so a cancelled run future does not leave it set
+struct RunningGuard(Arc<AtomicBool>);
+
+impl RunningGuard {
+    fn enter(running: &Arc<AtomicBool>) -> Result<Self, RunError> {
+        if running.swap(true, Ordering::SeqCst) {
+            return Err(RunError::AlreadyRunning);
+        }
+        Ok(Self(running.clone()))
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+// remove `item` from the list of `key`, dropping the list once it is empty
+fn remove_from_list(lists: &mut AHashMap<FastStr, Vec<FastStr>>, key: &str, item: &str) -> bool {
+    let Some(list) = lists.get_mut(key) else {
+        return false;
+    };
+    let len = list.len();
+    list.retain(|name| name != item);
+    let removed = list.len() < len;
+    if list.is_empty() {
+        lists.remove(key);
+    }
+    removed
+}
+
+// panics carry a &str or a String, anything else has no message
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic payload".to_string(),
+        },
+    }
+}
+
+// the message of the error followed by the messages of its sources
+fn error_message(err: &(dyn std::error::Error + 'static)) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        message.push_str(": ");
+        message.push_str(&err.to_string());
+        source = err.source();
+    }
+    message
+}
+
+// 32 hex digits from the wall clock and a random number, unique enough to tell runs apart
+fn new_run_id() -> FastStr {
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+    let random = ahash::RandomState::new().hash_one(SEQ.fetch_add(1, Ordering::Relaxed));
+    let micros = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64;
+    FastStr::new(format!("{:016x}{:016x}", micros, random))
+}
+
+type BoxExectorFuture = Pin<Box<dyn Future<Output = ExectorResult> + Send>>;
+
+struct NodeResult {
+    name: FastStr,
+    attempts: u32,
+    history: Vec<AttemptInfo>,
+    res: ExectorResult,
+    finish: Finish,
+}
+
+// how a node without error ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Finish {
+    Ran,
+    // a middlerware returned Ok without calling the exector
+    ShortCircuited,
+    // the exector returned `NodeControl::StopBranch`
+    StoppedBranch,
+    // the async condition said no, or failed with `ConditionErrorMode::Skip`
+    ConditionFalse,
+    // the start delay is over and the exector is ready to be spawned, it did not execute yet
+    Delayed,
+}
+
+async fn execute_with_timeout(
+    name: &FastStr,
+    exector_future: BoxExectorFuture,
+    node_timeout_ms: Option<u64>,
+) -> ExectorResult {
+    match node_timeout_ms {
+        Some(timeout_ms) => tokio::time::timeout(Duration::from_millis(timeout_ms), exector_future)
+            .await
+            .unwrap_or_else(|err| {
+                Err(format!(
+                    "exector {} timeout, time limit is {} ms, err is {:?}",
+                    name, timeout_ms, err
+                )
+                .into())
+            }),
+        None => exector_future.await,
     }
 }
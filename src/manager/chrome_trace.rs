@@ -0,0 +1,73 @@
+use std::fmt::Write;
+
+use crate::tracing_info::Status;
+
+use super::Manager;
+
+impl Manager {
+    // chrome trace event json of the latest run, for chrome://tracing or perfetto.
+    // finished nodes are complete events, running nodes are begin events without an end.
+    // overlapping nodes go to different threads so the parallelism shows as lanes
+    pub fn to_chrome_trace(&self) -> String {
+        let mut timings: Vec<_> = self
+            ._tracing
+            .snapshot()
+            .into_iter()
+            .filter(|timing| matches!(timing.status, Status::Done | Status::Failed | Status::Doing))
+            .collect();
+        timings.sort_by(|a, b| (a.start_micros, &a.name).cmp(&(b.start_micros, &b.name)));
+
+        // end time of the last node on each lane, a running node keeps its lane
+        let mut lane_ends: Vec<i64> = Vec::new();
+        let mut events = Vec::with_capacity(timings.len());
+        for timing in timings {
+            let end = match timing.status {
+                Status::Doing => i64::MAX,
+                _ => timing.end_micros,
+            };
+            let tid = match lane_ends
+                .iter()
+                .position(|&lane_end| lane_end <= timing.start_micros)
+            {
+                Some(tid) => {
+                    lane_ends[tid] = end;
+                    tid
+                }
+                None => {
+                    lane_ends.push(end);
+                    lane_ends.len() - 1
+                }
+            };
+
+            let mut event = format!("{{\"name\":{}", quote(&timing.name));
+            match timing.status {
+                Status::Doing => write!(event, ",\"ph\":\"B\",\"ts\":{}", timing.start_micros),
+                _ => write!(
+                    event,
+                    ",\"ph\":\"X\",\"ts\":{},\"dur\":{}",
+                    timing.start_micros,
+                    (timing.end_micros - timing.start_micros).max(0)
+                ),
+            }
+            .unwrap();
+            write!(event, ",\"pid\":1,\"tid\":{}}}", tid).unwrap();
+            events.push(event);
+        }
+
+        format!("[{}]", events.join(","))
+    }
+}
+
+fn quote(name: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in name.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use ahash::AHashMap;
+use faststr::FastStr;
+
+use super::Manager;
+use crate::report::longest_path;
+
+impl Manager {
+    // the chain of dependencies with the longest total duration in the latest run,
+    // nodes that did not finish weigh nothing
+    pub fn critical_path(&self) -> anyhow::Result<(Vec<FastStr>, Duration)> {
+        let timings: AHashMap<_, _> = self
+            .tracing_snapshot()
+            .into_iter()
+            .filter_map(|timing| Some((timing.name.clone(), timing.duration()?)))
+            .collect();
+        if timings.is_empty() {
+            return Err(anyhow::anyhow!("no completed run to analyze"));
+        }
+
+        let order = self.topo_order()?;
+        Ok(longest_path(&order, |name| self.deps_of(name), &timings))
+    }
+}
@@ -0,0 +1,68 @@
+use std::fmt::Write;
+
+use crate::tracing_info::Status;
+
+use super::Manager;
+
+impl Manager {
+    // graphviz dot of the dependency graph
+    pub fn to_dot(&self) -> String {
+        self.build_dot(false)
+    }
+
+    // same as to_dot, with nodes colored by the status of the latest run
+    pub fn to_dot_with_status(&self) -> String {
+        self.build_dot(true)
+    }
+
+    fn build_dot(&self, with_status: bool) -> String {
+        let mut nodes: Vec<_> = self.exectors.keys().collect();
+        nodes.sort_unstable();
+        let mut edges: Vec<_> = self
+            .adjacency_list
+            .iter()
+            .flat_map(|(from, to_list)| to_list.iter().map(move |to| (from, to)))
+            .collect();
+        edges.sort_unstable();
+
+        let mut dot = String::from("digraph cycle_loader {\n");
+        for node in nodes {
+            let mut attributes = vec![format!("label={}", quote(node))];
+            for (key, value) in self.node_meta.get(node).into_iter().flatten() {
+                attributes.push(format!("{}={}", quote(key), quote(value)));
+            }
+            let status = self
+                ._tracing
+                .tracing_infos
+                .get(node)
+                .filter(|_| with_status)
+                .map(|tracing_info| &tracing_info.status);
+            if let Some(status) = status {
+                attributes.push("style=filled".to_string());
+                attributes.push(format!("fillcolor={}", status_color(status)));
+            }
+            writeln!(dot, "    {} [{}];", quote(node), attributes.join(", ")).unwrap();
+        }
+        for (from, to) in edges {
+            writeln!(dot, "    {} -> {};", quote(from), quote(to)).unwrap();
+        }
+        dot.push('}');
+
+        dot
+    }
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn status_color(status: &Status) -> &'static str {
+    match status {
+        Status::NotStarted => "white",
+        Status::Doing => "yellow",
+        Status::Done => "green",
+        Status::Failed => "red",
+        Status::Skipped => "gray",
+        Status::Cancelled => "orange",
+    }
+}
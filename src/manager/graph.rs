@@ -0,0 +1,310 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
+
+use ahash::{AHashMap, AHashSet};
+use faststr::FastStr;
+
+use super::{Manager, RunError};
+
+// the shape of the graph over the registered exectors, edges to unknown names are left out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GraphStats {
+    pub nodes: usize,
+    pub edges: usize,
+    pub max_fan_in: usize,
+    pub max_fan_out: usize,
+    // edges on the longest chain of deps, unlike `critical_path` it ignores timings
+    pub longest_path_len: usize,
+    // exectors without deps
+    pub start_nodes: usize,
+    // exectors nothing depends on
+    pub leaf_nodes: usize,
+}
+
+// what is known about one exector without running it, deps and dependents are sorted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeView {
+    pub name: FastStr,
+    pub deps: Vec<FastStr>,
+    pub dependents: Vec<FastStr>,
+    pub priority: i32,
+    // labels from `set_node_meta`
+    pub meta: BTreeMap<FastStr, String>,
+}
+
+impl Manager {
+    // the waves a run would schedule without limits: every wave holds the exectors whose deps
+    // are all done by the earlier waves, nothing is executed
+    pub fn plan(&self) -> anyhow::Result<Vec<Vec<FastStr>>> {
+        let mut wave = self.pre_check_and_find_start_nodes()?;
+        let mut ready: AHashSet<FastStr> = AHashSet::new();
+        let mut waves = Vec::new();
+        while !wave.is_empty() {
+            wave.sort_unstable();
+            wave.dedup();
+            ready.extend(wave.iter().cloned());
+
+            let next_wave: Vec<FastStr> = wave
+                .iter()
+                .flat_map(|name| self.adjacency_list.get(name).into_iter().flatten())
+                .filter(|next| {
+                    !ready.contains(*next)
+                        && self
+                            .rev_adjacency_list
+                            .get(*next)
+                            .into_iter()
+                            .flatten()
+                            .all(|dep| ready.contains(dep))
+                })
+                .cloned()
+                .collect();
+            waves.push(std::mem::replace(&mut wave, next_wave));
+        }
+
+        Ok(waves)
+    }
+
+    // a linear order where every exector comes after its deps, found with kahn's algorithm,
+    // ties are broken by name so the order is stable
+    pub fn topo_order(&self) -> anyhow::Result<Vec<FastStr>> {
+        let mut indegrees: AHashMap<&FastStr, usize> = self
+            .exectors
+            .keys()
+            .map(|name| (name, self.deps_of(name).count()))
+            .collect();
+        let mut ready: BTreeSet<&FastStr> = indegrees
+            .iter()
+            .filter(|(_, indegree)| **indegree == 0)
+            .map(|(name, _)| *name)
+            .collect();
+
+        let mut order = Vec::with_capacity(self.exectors.len());
+        while let Some(name) = ready.pop_first() {
+            order.push(name.clone());
+            for next in self.dependents_of(name) {
+                let indegree = indegrees.get_mut(next).unwrap();
+                *indegree -= 1;
+                if *indegree == 0 {
+                    ready.insert(next);
+                }
+            }
+        }
+
+        if order.len() < self.exectors.len() {
+            let mut blocked: Vec<_> = indegrees
+                .into_iter()
+                .filter(|(_, indegree)| *indegree > 0)
+                .map(|(name, _)| name.as_str())
+                .collect();
+            blocked.sort_unstable();
+            return Err(anyhow::anyhow!(
+                "find cycle, no valid order for: {}",
+                blocked.join(", ")
+            ));
+        }
+
+        Ok(order)
+    }
+
+    // the exectors in `topo_order` for inspection, errors on a cycle like `topo_order`
+    pub fn iter_topo(&self) -> anyhow::Result<impl Iterator<Item = NodeView> + '_> {
+        Ok(self.topo_order()?.into_iter().map(|name| NodeView {
+            deps: self.deps(&name),
+            dependents: self.dependents(&name),
+            priority: self.priorities.get(&name).copied().unwrap_or(0),
+            meta: self.node_meta.get(&name).cloned().unwrap_or_default(),
+            name,
+        }))
+    }
+
+    // counted from the edges without running anything, errors on a cycle like `topo_order`
+    pub fn graph_stats(&self) -> anyhow::Result<GraphStats> {
+        let mut stats = GraphStats {
+            nodes: self.exectors.len(),
+            ..GraphStats::default()
+        };
+        // edges on the longest chain ending at each exector
+        let mut depths: AHashMap<FastStr, usize> = AHashMap::new();
+        for name in self.topo_order()? {
+            let fan_in = self.deps_of(&name).count();
+            let fan_out = self.dependents_of(&name).count();
+            stats.edges += fan_out;
+            stats.max_fan_in = stats.max_fan_in.max(fan_in);
+            stats.max_fan_out = stats.max_fan_out.max(fan_out);
+            stats.start_nodes += usize::from(fan_in == 0);
+            stats.leaf_nodes += usize::from(fan_out == 0);
+
+            let depth = self
+                .deps_of(&name)
+                .map(|dep| depths[dep] + 1)
+                .max()
+                .unwrap_or(0);
+            stats.longest_path_len = stats.longest_path_len.max(depth);
+            depths.insert(name, depth);
+        }
+
+        Ok(stats)
+    }
+
+    // exectors connected by edges in either direction, each sorted and ordered by their first
+    // name. the index of a component is what `run_component` takes
+    pub fn components(&self) -> Vec<Vec<FastStr>> {
+        let mut names: Vec<&FastStr> = self.exectors.keys().collect();
+        names.sort_unstable();
+        let mut seen: AHashSet<&FastStr> = AHashSet::new();
+        let mut components = Vec::new();
+        for name in names {
+            if !seen.insert(name) {
+                continue;
+            }
+            let mut component = vec![name.clone()];
+            let mut stack = vec![name];
+            while let Some(name) = stack.pop() {
+                for next in self.deps_of(name).chain(self.dependents_of(name)) {
+                    if seen.insert(next) {
+                        component.push(next.clone());
+                        stack.push(next);
+                    }
+                }
+            }
+            component.sort_unstable();
+            components.push(component);
+        }
+
+        components
+    }
+
+    // split the run budget over the exectors with an expected duration: each gets the share of
+    // its estimate in the heaviest chain of estimates through it, so no chain sums past the
+    // budget. the rest get no budget of their own. errors on a cycle like `topo_order`
+    pub(crate) fn budget_timeouts(
+        &self,
+        budget: Duration,
+    ) -> anyhow::Result<AHashMap<FastStr, u64>> {
+        let expected = |name: &FastStr| {
+            self.expected_durations
+                .get(name)
+                .map_or(0, |duration| duration.as_micros())
+        };
+        let order = self.topo_order()?;
+        // heaviest chain ending at and starting from each exector, both including it
+        let mut down: AHashMap<&FastStr, u128> = AHashMap::new();
+        for name in &order {
+            let before = self.deps_of(name).map(|dep| down[dep]).max().unwrap_or(0);
+            down.insert(name, before + expected(name));
+        }
+        let mut up: AHashMap<&FastStr, u128> = AHashMap::new();
+        for name in order.iter().rev() {
+            let after = self
+                .dependents_of(name)
+                .map(|next| up[next])
+                .max()
+                .unwrap_or(0);
+            up.insert(name, after + expected(name));
+        }
+
+        let budget_ms = budget.as_millis();
+        Ok(order
+            .iter()
+            .filter(|name| expected(name) > 0)
+            .map(|name| {
+                let chain = down[name] + up[name] - expected(name);
+                let share = budget_ms * expected(name) / chain;
+                (name.clone(), share.max(1) as u64)
+            })
+            .collect())
+    }
+
+    // a chain of edges from `from` to `to`, both included
+    pub(crate) fn find_path(&self, from: &FastStr, to: &FastStr) -> Option<Vec<FastStr>> {
+        let mut parents: AHashMap<&FastStr, &FastStr> = AHashMap::new();
+        let mut stack = vec![from];
+        while let Some(name) = stack.pop() {
+            if name == to {
+                let mut path = vec![name.clone()];
+                let mut name = name;
+                while let Some(parent) = parents.get(name) {
+                    path.push((*parent).clone());
+                    name = parent;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for next in self.adjacency_list.get(name).into_iter().flatten() {
+                if next != from && !parents.contains_key(next) {
+                    parents.insert(next, name);
+                    stack.push(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    // the targets with every exector they depend on, directly or not
+    pub(crate) fn dependency_closure(
+        &self,
+        targets: &[FastStr],
+    ) -> Result<AHashSet<FastStr>, RunError> {
+        let mut closure = AHashSet::new();
+        let mut stack = Vec::new();
+        for target in targets {
+            if !self.exectors.contains_key(target) {
+                return Err(RunError::Validation(format!(
+                    "target {} is not an exector",
+                    target
+                )));
+            }
+            if closure.insert(target.clone()) {
+                stack.push(target.clone());
+            }
+        }
+        while let Some(name) = stack.pop() {
+            for dep in self.rev_adjacency_list.get(&name).into_iter().flatten() {
+                if closure.insert(dep.clone()) {
+                    stack.push(dep.clone());
+                }
+            }
+        }
+
+        Ok(closure)
+    }
+
+    // direct deps of the exector as recorded by the edges, sorted
+    pub fn deps(&self, name: &str) -> Vec<FastStr> {
+        let mut deps = self
+            .rev_adjacency_list
+            .get(name)
+            .cloned()
+            .unwrap_or_default();
+        deps.sort_unstable();
+        deps
+    }
+
+    // exectors that depend directly on this one as recorded by the edges, sorted
+    pub fn dependents(&self, name: &str) -> Vec<FastStr> {
+        let mut dependents = self.adjacency_list.get(name).cloned().unwrap_or_default();
+        dependents.sort_unstable();
+        dependents
+    }
+
+    // deps that are registered exectors
+    pub(crate) fn deps_of<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a FastStr> {
+        self.rev_adjacency_list
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|dep| self.exectors.contains_key(*dep))
+    }
+
+    // dependents that are registered exectors
+    pub(crate) fn dependents_of<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a FastStr> {
+        self.adjacency_list
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|next| self.exectors.contains_key(*next))
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry};
+
+#[derive(Clone)]
+pub(crate) struct Metrics {
+    node_duration: HistogramVec,
+    node_failures: IntCounterVec,
+    in_flight: IntGauge,
+}
+
+impl Metrics {
+    pub(crate) fn register(registry: &Registry) -> prometheus::Result<Self> {
+        let node_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "cycle_loader_node_duration_seconds",
+                "duration of every exector run, retries included",
+            ),
+            &["name"],
+        )?;
+        let node_failures = IntCounterVec::new(
+            Opts::new(
+                "cycle_loader_node_failures_total",
+                "exectors that failed after all attempts",
+            ),
+            &["name"],
+        )?;
+        let in_flight = IntGauge::new(
+            "cycle_loader_nodes_in_flight",
+            "exectors that are running right now",
+        )?;
+
+        registry.register(Box::new(node_duration.clone()))?;
+        registry.register(Box::new(node_failures.clone()))?;
+        registry.register(Box::new(in_flight.clone()))?;
+
+        Ok(Self {
+            node_duration,
+            node_failures,
+            in_flight,
+        })
+    }
+
+    pub(crate) fn start(&self) -> InFlightGuard {
+        self.in_flight.inc();
+        InFlightGuard {
+            in_flight: self.in_flight.clone(),
+        }
+    }
+
+    pub(crate) fn finish(&self, name: &str, duration: Duration, failed: bool) {
+        self.node_duration
+            .with_label_values(&[name])
+            .observe(duration.as_secs_f64());
+        if failed {
+            self.node_failures.with_label_values(&[name]).inc();
+        }
+    }
+}
+
+// decrements the in-flight gauge even if the task is aborted
+pub(crate) struct InFlightGuard {
+    in_flight: IntGauge,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.in_flight.dec();
+    }
+}
@@ -1,9 +1,135 @@
-use std::{future::Future, pin::Pin};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
+use async_trait::async_trait;
+use faststr::FastStr;
 
-use crate::exector::Executor;
+use crate::{
+    context::Context,
+    exector::{ExectorError, ExectorResult, Executor},
+};
 
+pub type MiddlerwareFuture = Pin<Box<dyn Future<Output = ExectorResult> + Send>>;
+
+// receive the node name, the wrapped exector and the run context. a middlerware that returns
+// Ok without calling the exector short circuits the node, e.g. to serve a cached result. the
+// node is then reported as Skipped with `cached` set and its next exectors run as if it was done
 pub type Middlerware =
-    Box<dyn (
-        Fn(Box<dyn Executor>) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>
-    )>;
+    Box<dyn Fn(FastStr, Arc<dyn Executor>, Arc<Context>) -> MiddlerwareFuture + Send + Sync>;
+
+// a middlerware whose setup can fail before the wrapped exector is called,
+// the error fails the node as a `MiddlerwareSetupError` and is never retried
+pub type TryMiddlerware = Box<
+    dyn Fn(FastStr, Arc<dyn Executor>, Arc<Context>) -> Result<MiddlerwareFuture, ExectorError>
+        + Send
+        + Sync,
+>;
+
+// the node failed while setting up a middlerware, the exector itself did not run
+#[derive(Debug)]
+pub struct MiddlerwareSetupError {
+    pub name: FastStr,
+    pub source: ExectorError,
+}
+
+impl fmt::Display for MiddlerwareSetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "middlerware setup failed for exector {}: {}",
+            self.name, self.source
+        )
+    }
+}
+
+impl std::error::Error for MiddlerwareSetupError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+pub fn from_try(try_middlerware: TryMiddlerware) -> Middlerware {
+    Box::new(
+        move |name, exector, ctx| match try_middlerware(name.clone(), exector, ctx) {
+            Ok(future) => future,
+            Err(source) => {
+                Box::pin(async move { Err(MiddlerwareSetupError { name, source }.into()) })
+            }
+        },
+    )
+}
+
+// for middlerwares that do not care about the node name
+pub fn name_agnostic<F>(f: F) -> Middlerware
+where
+    F: Fn(Arc<dyn Executor>, Arc<Context>) -> MiddlerwareFuture + Send + Sync + 'static,
+{
+    Box::new(move |_, exector, ctx| f(exector, ctx))
+}
+
+// an exector whose execute goes through one middlerware, so middlerwares can wrap each other
+struct MiddlerwareExector {
+    inner: Arc<dyn Executor>,
+    middlerware: Arc<Middlerware>,
+    ctx: Arc<Context>,
+}
+
+#[async_trait]
+impl Executor for MiddlerwareExector {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        (self.middlerware)(self.inner.name(), self.inner.clone(), self.ctx.clone()).await
+    }
+
+    fn name(&self) -> FastStr {
+        self.inner.name()
+    }
+}
+
+// the innermost exector of a chain, notes whether any middlerware called through to it
+struct ReachedExector {
+    inner: Arc<dyn Executor>,
+    reached: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl Executor for ReachedExector {
+    async fn execute(&self, ctx: &Context) -> ExectorResult {
+        self.reached.store(true, Ordering::SeqCst);
+        self.inner.execute(ctx).await
+    }
+
+    fn name(&self) -> FastStr {
+        self.inner.name()
+    }
+}
+
+pub(crate) fn track_reached(exector: Arc<dyn Executor>) -> (Arc<dyn Executor>, Arc<AtomicBool>) {
+    let reached = Arc::new(AtomicBool::new(false));
+    let exector = Arc::new(ReachedExector {
+        inner: exector,
+        reached: reached.clone(),
+    });
+    (exector, reached)
+}
+
+// wrap the exector with every middlerware, the last added middlerware is the outermost
+pub(crate) fn chain(
+    exector: Arc<dyn Executor>,
+    middlerwares: &[Arc<Middlerware>],
+    ctx: &Arc<Context>,
+) -> Arc<dyn Executor> {
+    middlerwares.iter().fold(exector, |inner, middlerware| {
+        Arc::new(MiddlerwareExector {
+            inner,
+            middlerware: middlerware.clone(),
+            ctx: ctx.clone(),
+        })
+    })
+}
@@ -0,0 +1,60 @@
+// typed node names, so a typo in the wiring fails to compile instead of failing the run
+
+// used by `define_nodes!` in the caller's crate
+#[doc(hidden)]
+pub use faststr::FastStr;
+
+/// Defines an enum whose variants are node names, named `Node` unless given.
+/// A variant turns into its own name, so it goes wherever the manager takes a name.
+///
+/// ```
+/// cycle_loader::define_nodes! { Load, Transform, Store }
+///
+/// let mut manager = cycle_loader::manager::Manager::default();
+/// manager.add_deps(Node::Store, vec![Node::Transform]);
+/// assert_eq!(Node::Load.as_str(), "Load");
+/// assert_eq!(Node::ALL.len(), 3);
+/// ```
+///
+/// An unknown node is a compile error:
+///
+/// ```compile_fail
+/// cycle_loader::define_nodes! { Load, Store }
+///
+/// let mut manager = cycle_loader::manager::Manager::default();
+/// manager.add_dep(Node::Store, Node::Laod);
+/// ```
+#[macro_export]
+macro_rules! define_nodes {
+    ($vis:vis enum $name:ident { $($node:ident),+ $(,)? }) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $($node),+
+        }
+
+        impl $name {
+            pub const ALL: &'static [$name] = &[$($name::$node),+];
+
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    $($name::$node => stringify!($node)),+
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl ::std::convert::From<$name> for $crate::nodes::FastStr {
+            fn from(node: $name) -> Self {
+                $crate::nodes::FastStr::from_static_str(node.as_str())
+            }
+        }
+    };
+    ($($node:ident),+ $(,)?) => {
+        $crate::define_nodes! { pub enum Node { $($node),+ } }
+    };
+}
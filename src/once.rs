@@ -0,0 +1,89 @@
+use std::sync::{Arc, Mutex};
+
+use ahash::AHashMap;
+use async_trait::async_trait;
+use faststr::FastStr;
+use tokio::sync::OnceCell;
+
+use crate::{
+    context::Context,
+    exector::{ExectorResult, Executor},
+    progress::ProgressReporter,
+};
+
+// remembers which exectors succeeded, shared by managers through an Arc so an exector marked
+// with `Manager::set_run_once` executes once across all of them. a scheduling that finds the
+// exector running waits for that execution, a failed execution is not remembered and the next
+// scheduling tries again. an entry lives as long as the registry unless it is forgotten, and
+// values the exector put into the context only reach the run that executed it
+#[derive(Default)]
+pub struct OnceRegistry {
+    cells: Mutex<AHashMap<FastStr, Arc<OnceCell<()>>>>,
+}
+
+impl OnceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // whether the exector of this name already succeeded
+    pub fn has_run(&self, name: &str) -> bool {
+        self.cells
+            .lock()
+            .unwrap()
+            .get(name)
+            .is_some_and(|cell| cell.initialized())
+    }
+
+    // let the exector execute again on its next scheduling
+    pub fn forget(&self, name: &str) {
+        self.cells.lock().unwrap().remove(name);
+    }
+
+    pub fn clear(&self) {
+        self.cells.lock().unwrap().clear();
+    }
+
+    fn cell(&self, name: FastStr) -> Arc<OnceCell<()>> {
+        self.cells.lock().unwrap().entry(name).or_default().clone()
+    }
+}
+
+// executes the inner exector only while its cell is empty
+struct OnceExector {
+    inner: Arc<dyn Executor>,
+    cell: Arc<OnceCell<()>>,
+}
+
+#[async_trait]
+impl Executor for OnceExector {
+    async fn execute(&self, ctx: &Context) -> ExectorResult {
+        self.cell
+            .get_or_try_init(|| self.inner.execute(ctx))
+            .await
+            .map(|_| ())
+    }
+
+    async fn execute_with_progress(
+        &self,
+        ctx: &Context,
+        progress: &ProgressReporter,
+    ) -> ExectorResult {
+        self.cell
+            .get_or_try_init(|| self.inner.execute_with_progress(ctx, progress))
+            .await
+            .map(|_| ())
+    }
+
+    fn name(&self) -> FastStr {
+        self.inner.name()
+    }
+}
+
+pub(crate) fn run_once(exector: Arc<dyn Executor>, registry: &OnceRegistry) -> Arc<dyn Executor> {
+    let cell = registry.cell(exector.name());
+    Arc::new(OnceExector {
+        inner: exector,
+        cell,
+    })
+}
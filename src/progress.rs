@@ -0,0 +1,79 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use faststr::FastStr;
+
+use crate::{
+    channel::EventSink,
+    context::Context,
+    exector::{ExectorResult, Executor},
+};
+
+// sent through the progress channel while a run goes on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    Started(FastStr),
+    // percent done reported by the exector, at most 100
+    NodeProgress(FastStr, u8),
+    Finished(FastStr, Duration),
+    Failed(FastStr, String),
+    // dropped with a cancelled branch
+    Cancelled(FastStr),
+}
+
+// handed to `Executor::execute_with_progress`, reports go to the progress channel
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+    name: FastStr,
+    sender: Option<EventSink<ProgressEvent>>,
+}
+
+impl ProgressReporter {
+    pub(crate) fn new(name: FastStr, sender: Option<EventSink<ProgressEvent>>) -> Self {
+        Self { name, sender }
+    }
+
+    // a reporter that drops every report
+    pub fn disabled(name: impl Into<FastStr>) -> Self {
+        Self::new(name.into(), None)
+    }
+
+    // percentages above 100 are sent as 100
+    pub fn report(&self, percent: u8) {
+        if let Some(sender) = &self.sender {
+            sender.send_from_exector(ProgressEvent::NodeProgress(
+                self.name.clone(),
+                percent.min(100),
+            ));
+        }
+    }
+}
+
+// makes the plain `execute` of the exector go through `execute_with_progress`,
+// so middlerwares that call `execute` keep the progress reports
+struct ProgressExector {
+    inner: Arc<dyn Executor>,
+    reporter: ProgressReporter,
+}
+
+#[async_trait]
+impl Executor for ProgressExector {
+    async fn execute(&self, ctx: &Context) -> ExectorResult {
+        self.inner.execute_with_progress(ctx, &self.reporter).await
+    }
+
+    fn name(&self) -> FastStr {
+        self.inner.name()
+    }
+}
+
+pub(crate) fn with_progress(
+    exector: Arc<dyn Executor>,
+    sender: EventSink<ProgressEvent>,
+) -> Arc<dyn Executor> {
+    let reporter = ProgressReporter::new(exector.name(), Some(sender));
+    Arc::new(ProgressExector {
+        inner: exector,
+        reporter,
+    })
+}
@@ -0,0 +1,381 @@
+use std::time::Duration;
+
+use ahash::AHashMap;
+use faststr::FastStr;
+
+use crate::tracing_info::{AttemptInfo, NodeTiming, Status, TracingInfoManager};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    // start and end are timestamps in micros
+    Completed { start: i64, end: i64 },
+    Failed(String),
+    // failed, but marked with `Manager::set_non_critical` so the run went on as if it completed
+    FailedNonCritical(String),
+    Skipped,
+    // dropped with a cancelled branch, see `Manager::branch_token`
+    Cancelled,
+    InProgress { start: i64 },
+    NotStarted,
+}
+
+// handed to the node complete hook as soon as an exector finishes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeOutcome {
+    pub duration: Duration,
+    // error of the last attempt, None on success
+    pub error: Option<String>,
+    // dropped with a cancelled branch, see `Manager::branch_token`. a running target is
+    // aborted, so whatever it returned does not count
+    pub cancelled: bool,
+}
+
+impl NodeOutcome {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none() && !self.cancelled
+    }
+}
+
+// serializable with the serde feature, e.g. to archive runs and compare them later. a
+// deserialized report answers every query of a fresh one
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunReport {
+    pub outcomes: AHashMap<FastStr, Outcome>,
+    // set on reports returned by a run, see `Manager::run_labeled`
+    pub run_id: FastStr,
+    // the exector that failed first in the run and its error, a root cause candidate
+    pub first_error: Option<(FastStr, String)>,
+    // the attempts of every exector that executed, in order
+    pub attempts: AHashMap<FastStr, Vec<AttemptInfo>>,
+    // the deps of every exector that has any, so the report finds its critical path alone
+    pub deps: AHashMap<FastStr, Vec<FastStr>>,
+    // start and end in micros of every failed exector, also one that panicked or whose
+    // condition failed and so has no attempts
+    pub failed_spans: AHashMap<FastStr, (i64, i64)>,
+}
+
+impl RunReport {
+    pub(crate) fn from_tracing(tracing: &TracingInfoManager) -> Self {
+        let outcomes = tracing
+            .tracing_infos
+            .iter()
+            .map(|(name, tracing_info)| {
+                let outcome = match tracing_info.status {
+                    Status::NotStarted => Outcome::NotStarted,
+                    Status::Doing => Outcome::InProgress {
+                        start: tracing_info.start_time,
+                    },
+                    Status::Done => Outcome::Completed {
+                        start: tracing_info.start_time,
+                        end: tracing_info.end_time,
+                    },
+                    Status::Failed => {
+                        Outcome::Failed(tracing_info.error.clone().unwrap_or_default())
+                    }
+                    Status::Skipped => Outcome::Skipped,
+                    Status::Cancelled => Outcome::Cancelled,
+                };
+                (name.clone(), outcome)
+            })
+            .collect();
+        let attempts = tracing
+            .tracing_infos
+            .iter()
+            .filter(|(_, tracing_info)| !tracing_info.history.is_empty())
+            .map(|(name, tracing_info)| (name.clone(), tracing_info.history.clone()))
+            .collect();
+        let failed_spans = tracing
+            .tracing_infos
+            .iter()
+            .filter(|(_, tracing_info)| tracing_info.status == Status::Failed)
+            .map(|(name, tracing_info)| {
+                let span = (tracing_info.start_time, tracing_info.end_time);
+                (name.clone(), span)
+            })
+            .collect();
+
+        Self {
+            outcomes,
+            run_id: FastStr::empty(),
+            first_error: None,
+            attempts,
+            deps: AHashMap::new(),
+            failed_spans,
+        }
+    }
+
+    pub fn outcome(&self, name: &str) -> Option<&Outcome> {
+        self.outcomes.get(name)
+    }
+
+    pub fn completed(&self) -> Vec<FastStr> {
+        self.names_matching(|outcome| matches!(outcome, Outcome::Completed { .. }))
+    }
+
+    // completed nodes with how long they ran, sorted by name
+    pub fn completed_durations(&self) -> Vec<(FastStr, Duration)> {
+        let mut durations: Vec<_> = self
+            .outcomes
+            .iter()
+            .filter_map(|(name, outcome)| match outcome {
+                Outcome::Completed { start, end } => Some((
+                    name.clone(),
+                    Duration::from_micros((end - start).max(0) as u64),
+                )),
+                _ => None,
+            })
+            .collect();
+        durations.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        durations
+    }
+
+    pub fn failed(&self) -> Vec<FastStr> {
+        self.names_matching(|outcome| matches!(outcome, Outcome::Failed(_)))
+    }
+
+    // exectors that only succeeded after a retry, the flaky ones, sorted by name
+    pub fn retried(&self) -> Vec<FastStr> {
+        let mut names: Vec<_> = self
+            .attempts
+            .iter()
+            .filter(|(name, attempts)| {
+                attempts.len() > 1 && matches!(self.outcome(name), Some(Outcome::Completed { .. }))
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    pub fn failed_non_critical(&self) -> Vec<FastStr> {
+        self.names_matching(|outcome| matches!(outcome, Outcome::FailedNonCritical(_)))
+    }
+
+    pub fn skipped(&self) -> Vec<FastStr> {
+        self.names_matching(|outcome| matches!(outcome, Outcome::Skipped))
+    }
+
+    pub fn cancelled(&self) -> Vec<FastStr> {
+        self.names_matching(|outcome| matches!(outcome, Outcome::Cancelled))
+    }
+
+    pub fn in_progress(&self) -> Vec<FastStr> {
+        self.names_matching(|outcome| matches!(outcome, Outcome::InProgress { .. }))
+    }
+
+    pub fn not_started(&self) -> Vec<FastStr> {
+        self.names_matching(|outcome| matches!(outcome, Outcome::NotStarted))
+    }
+
+    // every node completed, a failed non critical node or a cancelled branch does not count
+    // against it
+    pub fn is_success(&self) -> bool {
+        self.outcomes.values().all(|outcome| {
+            matches!(
+                outcome,
+                Outcome::Completed { .. } | Outcome::FailedNonCritical(_) | Outcome::Cancelled
+            )
+        })
+    }
+
+    // like `Manager::run_stats`
+    pub fn stats(&self) -> RunStats {
+        RunStats::from_spans(self.spans().into_iter().map(|(_, start, end)| (start, end)))
+    }
+
+    // like `Manager::critical_path`, from the deps recorded in the report
+    pub fn critical_path(&self) -> anyhow::Result<(Vec<FastStr>, Duration)> {
+        let durations: AHashMap<_, _> = self
+            .spans()
+            .into_iter()
+            .map(|(name, start, end)| {
+                let duration = Duration::from_micros((end - start).max(0) as u64);
+                (name.clone(), duration)
+            })
+            .collect();
+        if durations.is_empty() {
+            return Err(anyhow::anyhow!("no completed run to analyze"));
+        }
+
+        let order = self.topo_order()?;
+        Ok(longest_path(
+            &order,
+            |name| self.deps.get(name).into_iter().flatten(),
+            &durations,
+        ))
+    }
+
+    // start and end of the nodes that finished
+    fn spans(&self) -> Vec<(&FastStr, i64, i64)> {
+        self.outcomes
+            .iter()
+            .filter_map(|(name, outcome)| match outcome {
+                Outcome::Completed { start, end } => Some((name, *start, *end)),
+                Outcome::Failed(_) | Outcome::FailedNonCritical(_) => {
+                    let (start, end) = self.failed_spans.get(name)?;
+                    Some((name, *start, *end))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn topo_order(&self) -> anyhow::Result<Vec<FastStr>> {
+        let mut remaining_deps: AHashMap<&FastStr, usize> = self
+            .outcomes
+            .keys()
+            .map(|name| (name, self.deps.get(name).map_or(0, Vec::len)))
+            .collect();
+        let mut dependents: AHashMap<&FastStr, Vec<&FastStr>> = AHashMap::new();
+        for (name, deps) in &self.deps {
+            for dep in deps {
+                dependents.entry(dep).or_default().push(name);
+            }
+        }
+
+        let mut ready: Vec<&FastStr> = remaining_deps
+            .iter()
+            .filter(|(_, remaining)| **remaining == 0)
+            .map(|(name, _)| *name)
+            .collect();
+        let mut order = Vec::with_capacity(remaining_deps.len());
+        while let Some(name) = ready.pop() {
+            order.push(name.clone());
+            for next in dependents.get(name).into_iter().flatten() {
+                if let Some(remaining) = remaining_deps.get_mut(next) {
+                    *remaining -= 1;
+                    if *remaining == 0 {
+                        ready.push(next);
+                    }
+                }
+            }
+        }
+        if order.len() < remaining_deps.len() {
+            return Err(anyhow::anyhow!(
+                "report deps form a cycle or name unknown nodes"
+            ));
+        }
+        Ok(order)
+    }
+
+    // sorted by name
+    fn names_matching(&self, f: impl Fn(&Outcome) -> bool) -> Vec<FastStr> {
+        let mut names: Vec<_> = self
+            .outcomes
+            .iter()
+            .filter(|(_, outcome)| f(outcome))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+// the chain through `order` with the longest total duration, a node missing from `durations`
+// weighs nothing. ties go to the smaller name
+pub(crate) fn longest_path<'a, I>(
+    order: &'a [FastStr],
+    deps_of: impl Fn(&FastStr) -> I,
+    durations: &AHashMap<FastStr, Duration>,
+) -> (Vec<FastStr>, Duration)
+where
+    I: Iterator<Item = &'a FastStr>,
+{
+    // longest duration of a path ending at the node, and the dep it comes from
+    let mut longest: AHashMap<&FastStr, (Duration, Option<&FastStr>)> = AHashMap::new();
+    for name in order {
+        let (dep_duration, prev) = deps_of(name)
+            .map(|dep| (longest[dep].0, Some(dep)))
+            .max_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1)))
+            .unwrap_or_default();
+        let duration = durations.get(name).copied().unwrap_or_default();
+        longest.insert(name, (dep_duration + duration, prev));
+    }
+
+    let (mut last, (total, _)) = order
+        .iter()
+        .map(|name| (name, longest[name]))
+        .max_by(|a, b| (a.1).0.cmp(&(b.1).0).then_with(|| b.0.cmp(a.0)))
+        .unwrap();
+    let mut path = vec![last.clone()];
+    while let (_, Some(prev)) = longest[last] {
+        path.push(prev.clone());
+        last = prev;
+    }
+    path.reverse();
+    (path, total)
+}
+
+// how well a run parallelized, from the nodes that finished. durations serialize as micros
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunStats {
+    // from the first start to the last end
+    #[cfg_attr(feature = "serde", serde(with = "duration_micros"))]
+    pub wall_clock: Duration,
+    // sum of the node durations
+    #[cfg_attr(feature = "serde", serde(with = "duration_micros"))]
+    pub total_node_time: Duration,
+    // total_node_time / wall_clock, 0 when nothing finished
+    pub parallelism: f64,
+}
+
+impl RunStats {
+    pub(crate) fn from_timings(timings: &[NodeTiming]) -> Self {
+        Self::from_spans(
+            timings
+                .iter()
+                .filter(|timing| timing.duration().is_some())
+                .map(|timing| (timing.start_micros, timing.end_micros)),
+        )
+    }
+
+    // start and end in micros of every finished node
+    fn from_spans(spans: impl Iterator<Item = (i64, i64)>) -> Self {
+        let spans: Vec<_> = spans.collect();
+        let (Some(first_start), Some(last_end)) = (
+            spans.iter().map(|(start, _)| *start).min(),
+            spans.iter().map(|(_, end)| *end).max(),
+        ) else {
+            return Self::default();
+        };
+
+        let wall_clock = Duration::from_micros((last_end - first_start).max(0) as u64);
+        let total_node_time: Duration = spans
+            .iter()
+            .map(|(start, end)| Duration::from_micros((end - start).max(0) as u64))
+            .sum();
+        let parallelism = if wall_clock.is_zero() {
+            0.0
+        } else {
+            total_node_time.as_secs_f64() / wall_clock.as_secs_f64()
+        };
+        Self {
+            wall_clock,
+            total_node_time,
+            parallelism,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod duration_micros {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        duration: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_micros() as u64)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_micros)
+    }
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Backoff {
+    // wait the same duration before every retry
+    Fixed(Duration),
+    // wait base, base * 2, base * 4, ... before each retry
+    Exponential(Duration),
+}
+
+impl Backoff {
+    // attempt is the number of the attempt that just failed, starting from 1
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        match self {
+            Backoff::Fixed(delay) => *delay,
+            Backoff::Exponential(base) => {
+                let factor = 1u32
+                    .checked_shl(attempt.saturating_sub(1))
+                    .unwrap_or(u32::MAX);
+                base.saturating_mul(factor)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub backoff: Backoff,
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use faststr::FastStr;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::exector::{ExectorError, Executor};
+
+pub(crate) struct ScheduleRequest {
+    pub(crate) exector: Arc<dyn Executor>,
+    pub(crate) deps: Vec<FastStr>,
+    pub(crate) reply: oneshot::Sender<anyhow::Result<()>>,
+}
+
+/// Put in the context of every run, lets a running exector add exectors to the graph it runs
+/// in, e.g. a crawler scheduling a fetch for every link it finds. A scheduled exector may only
+/// depend on exectors of the run that have not completed yet, the exector scheduling it
+/// included, so it can never close a cycle. Scheduled exectors stay in the manager for the
+/// report and tracing of the run and are removed when the next run starts or on `reset`.
+///
+/// ```
+/// use cycle_loader::{
+///     context::Context,
+///     exector::{fn_executor, Executor, ExectorResult},
+///     manager::Manager,
+///     scheduler::Scheduler,
+/// };
+/// use faststr::FastStr;
+///
+/// struct Crawl;
+///
+/// #[async_trait::async_trait]
+/// impl Executor for Crawl {
+///     async fn execute(&self, ctx: &Context) -> ExectorResult {
+///         let scheduler = ctx.get::<Scheduler>().unwrap();
+///         for page in ["a", "b"] {
+///             let fetch = fn_executor(format!("fetch_{}", page), || async { Ok(()) });
+///             scheduler.schedule(fetch, vec!["crawl"]).await?;
+///         }
+///         Ok(())
+///     }
+///
+///     fn name(&self) -> FastStr {
+///         "crawl".into()
+///     }
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let mut manager = Manager::new(1000);
+/// manager.add_exector(Box::new(Crawl));
+/// let report = manager.run_with_report().await.unwrap();
+/// assert_eq!(report.completed(), vec!["crawl", "fetch_a", "fetch_b"]);
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct Scheduler {
+    sender: mpsc::UnboundedSender<ScheduleRequest>,
+}
+
+pub(crate) fn channel() -> (Scheduler, mpsc::UnboundedReceiver<ScheduleRequest>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    (Scheduler { sender }, receiver)
+}
+
+impl Scheduler {
+    // add the exector with these deps on top of the ones it declares. errors when the name is
+    // taken, a dep is not part of the run or already completed, or a limit is reached
+    pub async fn schedule<T: Into<FastStr>>(
+        &self,
+        exector: Box<dyn Executor>,
+        deps: Vec<T>,
+    ) -> Result<(), ExectorError> {
+        let (reply, replied) = oneshot::channel();
+        let request = ScheduleRequest {
+            exector: Arc::from(exector),
+            deps: deps.into_iter().map(Into::into).collect(),
+            reply,
+        };
+        let run_over = || anyhow::anyhow!("run is over, nothing can be scheduled");
+        self.sender.send(request).map_err(|_| run_over())?;
+        replied.await.map_err(|_| run_over())?.map_err(Into::into)
+    }
+}
@@ -0,0 +1,28 @@
+// topology loaded from config, the exectors are still registered in code under the same names
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphSpec {
+    pub nodes: Vec<String>,
+    // (from, to), to depends on from
+    pub edges: Vec<(String, String)>,
+}
+
+impl GraphSpec {
+    pub(crate) fn check(&self) -> anyhow::Result<()> {
+        let mut undeclared: Vec<_> = self
+            .edges
+            .iter()
+            .filter(|(from, to)| !self.nodes.contains(from) || !self.nodes.contains(to))
+            .map(|(from, to)| format!("{} -> {}", from, to))
+            .collect();
+        if !undeclared.is_empty() {
+            undeclared.sort_unstable();
+            return Err(anyhow::anyhow!(
+                "edges reference undeclared nodes: {}",
+                undeclared.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+}
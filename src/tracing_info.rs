@@ -1,12 +1,34 @@
-use std::fmt::Display;
+use std::{collections::BTreeMap, fmt::Display, sync::Arc, time::Duration};
 
 use ahash::AHashMap;
 use chrono::Local;
+use faststr::FastStr;
 
-pub(crate) enum Status {
+// source of every tracing timestamp, swap it for a mock to get exact durations in tests
+pub trait Clock: Send + Sync {
+    fn now_micros(&self) -> i64;
+}
+
+// local wall clock, the default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_micros(&self) -> i64 {
+        Local::now().timestamp_micros()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Status {
     NotStarted,
     Doing,
     Done,
+    Failed,
+    Skipped,
+    // dropped with a branch cancelled by `Manager::branch_token`
+    Cancelled,
 }
 
 impl Display for Status {
@@ -15,31 +37,66 @@ impl Display for Status {
             Status::NotStarted => write!(f, "NotStarted"),
             Status::Doing => write!(f, "Doing"),
             Status::Done => write!(f, "Done"),
+            Status::Failed => write!(f, "Failed"),
+            Status::Skipped => write!(f, "Skipped"),
+            Status::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
 
+// one execution of an exector, a retried node has one per try. timestamps are in micros
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttemptInfo {
+    pub start_micros: i64,
+    pub end_micros: i64,
+    // None when the attempt succeeded
+    pub error: Option<String>,
+}
+
+impl AttemptInfo {
+    pub fn duration(&self) -> Duration {
+        Duration::from_micros((self.end_micros - self.start_micros).max(0) as u64)
+    }
+}
+
 pub(crate) struct TracingInfo {
     pub(crate) status: Status,
+    // when all deps were done, start_time minus ready_time is the wait for a concurrency slot
+    pub(crate) ready_time: i64,
     pub(crate) start_time: i64,
     pub(crate) end_time: i64,
+    pub(crate) attempts: u32,
+    pub(crate) error: Option<String>,
+    // skipped because the result of a previous run is still valid
+    pub(crate) cached: bool,
+    // every attempt in order, the node start and end span all of them
+    pub(crate) history: Vec<AttemptInfo>,
 }
 
 impl Display for TracingInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.status {
-            Status::Doing => {
-                let now = Local::now().timestamp_micros();
-                write!(
-                    f,
-                    "status: {}, start_time: {}, now: {}",
-                    self.status, self.start_time, now
-                )
-            }
+            // the manager appends the current time, it owns the clock
+            Status::Doing => write!(
+                f,
+                "status: {}, ready_time: {}, start_time: {}",
+                self.status, self.ready_time, self.start_time
+            ),
+            Status::Failed => write!(
+                f,
+                "status: {}, ready_time: {}, start_time: {}, end_time: {}, attempts: {}, error: {}",
+                self.status,
+                self.ready_time,
+                self.start_time,
+                self.end_time,
+                self.attempts,
+                self.error.as_deref().unwrap_or_default()
+            ),
             _ => write!(
                 f,
-                "status: {}, start_time: {}, end_time: {}",
-                self.status, self.start_time, self.end_time
+                "status: {}, ready_time: {}, start_time: {}, end_time: {}, attempts: {}",
+                self.status, self.ready_time, self.start_time, self.end_time, self.attempts
             ),
         }
     }
@@ -49,82 +106,413 @@ impl TracingInfo {
     pub(crate) fn new() -> Self {
         Self {
             status: Status::NotStarted,
+            ready_time: 0,
             start_time: 0,
             end_time: 0,
+            attempts: 0,
+            error: None,
+            cached: false,
+            history: Vec::new(),
         }
     }
 
-    pub(crate) fn start(&mut self) {
+    // the status stays NotStarted until the exector gets a slot and starts
+    pub(crate) fn ready(&mut self, now: i64) {
+        match self.status {
+            Status::NotStarted if self.ready_time == 0 => {
+                self.ready_time = now;
+            }
+            _ => {
+                tracing::warn!("ready failed, status: {}", self.status);
+            }
+        }
+    }
+
+    pub(crate) fn start(&mut self, now: i64) {
         match self.status {
             Status::NotStarted => {
                 self.status = Status::Doing;
-                self.start_time = Local::now().timestamp_micros();
+                self.start_time = now;
+                if self.ready_time == 0 {
+                    self.ready_time = self.start_time;
+                }
             }
             _ => {
                 tracing::warn!("start failed, status: {}", self.status);
             }
-            
         }
     }
 
-    pub(crate) fn done(&mut self) {
+    pub(crate) fn skip(&mut self) {
+        match self.status {
+            Status::NotStarted => {
+                self.status = Status::Skipped;
+            }
+            _ => {
+                tracing::warn!("skip failed, status: {}", self.status);
+            }
+        }
+    }
+
+    // skipped after it started, by a middlerware or an async condition
+    pub(crate) fn skip_started(&mut self, now: i64, attempts: u32) {
+        match self.status {
+            Status::Doing => {
+                self.status = Status::Skipped;
+                self.end_time = now;
+                self.attempts = attempts;
+            }
+            _ => {
+                tracing::warn!("skip started failed, status: {}", self.status);
+            }
+        }
+    }
+
+    pub(crate) fn fail(&mut self, now: i64, attempts: u32, error: String) {
+        match self.status {
+            Status::Doing => {
+                self.status = Status::Failed;
+                self.end_time = now;
+                self.attempts = attempts;
+                self.error = Some(error);
+            }
+            _ => {
+                tracing::warn!("fail failed, status: {}", self.status);
+            }
+        }
+    }
+
+    pub(crate) fn done(&mut self, now: i64, attempts: u32) {
         match self.status {
             Status::Doing => {
                 self.status = Status::Done;
-                self.end_time = Local::now().timestamp_micros();
+                self.end_time = now;
+                self.attempts = attempts;
             }
             _ => {
                 tracing::warn!("done failed, status: {}", self.status);
             }
         }
     }
+
+    // a running exector is aborted, the end time tells when
+    pub(crate) fn cancel(&mut self, now: i64) {
+        match self.status {
+            Status::NotStarted => {
+                self.status = Status::Cancelled;
+            }
+            Status::Doing => {
+                self.status = Status::Cancelled;
+                self.end_time = now;
+            }
+            _ => {
+                tracing::warn!("cancel failed, status: {}", self.status);
+            }
+        }
+    }
+}
+
+// how many nodes of the current run reached each state, kept with or without per node tracing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RunCounts {
+    pub done: usize,
+    pub failed: usize,
+    // for any reason, including cached and short circuited nodes
+    pub skipped: usize,
+    pub cancelled: usize,
+    pub in_progress: usize,
+}
+
+impl RunCounts {
+    pub fn finished(&self) -> usize {
+        self.done + self.failed + self.skipped + self.cancelled
+    }
+}
+
+impl Display for RunCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "done: {}, failed: {}, skipped: {}, cancelled: {}, in_progress: {}",
+            self.done, self.failed, self.skipped, self.cancelled, self.in_progress
+        )
+    }
 }
 
 pub struct TracingInfoManager {
-    pub(crate) tracing_infos: AHashMap<&'static str, TracingInfo>,
+    pub(crate) tracing_infos: AHashMap<FastStr, TracingInfo>,
+    clock: Arc<dyn Clock>,
+    // without it only the counts are kept and `tracing_infos` stays empty
+    enabled: bool,
+    counts: RunCounts,
 }
 
 impl Display for TracingInfoManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (key, tracing_info) in self.tracing_infos.iter() {
-            write!(f, "key: {}, tracing_info {}; ", key, tracing_info)?;
+        if !self.enabled {
+            return write!(f, "{}", self.counts);
+        }
+        let mut keys: Vec<_> = self.tracing_infos.keys().collect();
+        keys.sort_unstable();
+        for key in keys {
+            let tracing_info = &self.tracing_infos[key];
+            write!(f, "key: {}, tracing_info {}", key, tracing_info)?;
+            if tracing_info.status == Status::Doing {
+                write!(f, ", now: {}", self.clock.now_micros())?;
+            }
+            write!(f, "; ")?;
         }
 
         Ok(())
     }
-    
 }
 
 impl TracingInfoManager {
     pub(crate) fn new() -> Self {
         Self {
             tracing_infos: AHashMap::new(),
+            clock: Arc::new(SystemClock),
+            enabled: true,
+            counts: RunCounts::default(),
         }
     }
 
-    pub(crate) fn add_tracing_info(&mut self, key: &'static str) {
+    // turning it off drops every tracing info, turning it on registers the keys again
+    pub(crate) fn set_enabled<'a>(
+        &mut self,
+        enabled: bool,
+        keys: impl Iterator<Item = &'a FastStr>,
+    ) {
+        self.enabled = enabled;
+        self.tracing_infos = AHashMap::new();
+        if enabled {
+            for key in keys {
+                self.add_tracing_info(key.clone());
+            }
+        }
+    }
+
+    pub(crate) fn counts(&self) -> RunCounts {
+        self.counts
+    }
+
+    pub(crate) fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    pub(crate) fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    pub(crate) fn add_tracing_info(&mut self, key: FastStr) {
+        if !self.enabled {
+            return;
+        }
         self.tracing_infos.insert(key, TracingInfo::new());
     }
 
-    pub(crate) fn start(&mut self, key: &'static str) {
-        if let Some(tracing_info) = self.tracing_infos.get_mut(key) {
-            tracing_info.start();
-        } else {
-            tracing::warn!("start failed, key: {} not found", key);
+    pub(crate) fn remove_tracing_info(&mut self, key: &str) {
+        self.tracing_infos.remove(key);
+    }
+
+    // reset every tracing info to NotStarted, keep the registered keys
+    pub(crate) fn reset(&mut self) {
+        self.counts = RunCounts::default();
+        for tracing_info in self.tracing_infos.values_mut() {
+            *tracing_info = TracingInfo::new();
+        }
+    }
+
+    pub(crate) fn ready(&mut self, key: &FastStr) {
+        let now = self.clock.now_micros();
+        if let Some(tracing_info) = self.get_or_add_tracing_info(key) {
+            tracing_info.ready(now);
+        }
+    }
+
+    pub(crate) fn start(&mut self, key: &FastStr) {
+        self.counts.in_progress += 1;
+        let now = self.clock.now_micros();
+        if let Some(tracing_info) = self.get_or_add_tracing_info(key) {
+            tracing_info.start(now);
+        }
+    }
+
+    pub(crate) fn done(&mut self, key: &FastStr, attempts: u32) {
+        self.counts.in_progress = self.counts.in_progress.saturating_sub(1);
+        self.counts.done += 1;
+        let now = self.clock.now_micros();
+        if let Some(tracing_info) = self.get_or_add_tracing_info(key) {
+            tracing_info.done(now, attempts);
+        }
+    }
+
+    pub(crate) fn skip(&mut self, key: &FastStr) {
+        self.counts.skipped += 1;
+        if let Some(tracing_info) = self.get_or_add_tracing_info(key) {
+            tracing_info.skip();
+        }
+    }
+
+    pub(crate) fn skip_cached(&mut self, key: &FastStr) {
+        self.counts.skipped += 1;
+        if let Some(tracing_info) = self.get_or_add_tracing_info(key) {
+            tracing_info.skip();
+            tracing_info.cached = true;
+        }
+    }
+
+    pub(crate) fn skip_started(&mut self, key: &FastStr, attempts: u32) {
+        self.counts.in_progress = self.counts.in_progress.saturating_sub(1);
+        self.counts.skipped += 1;
+        let now = self.clock.now_micros();
+        if let Some(tracing_info) = self.get_or_add_tracing_info(key) {
+            tracing_info.skip_started(now, attempts);
+        }
+    }
+
+    // a middlerware finished the node without calling the exector
+    pub(crate) fn short_circuit(&mut self, key: &FastStr, attempts: u32) {
+        self.skip_started(key, attempts);
+        if let Some(tracing_info) = self.get_or_add_tracing_info(key) {
+            tracing_info.cached = true;
         }
     }
 
-    pub(crate) fn done(&mut self, key: &'static str) {
-        if let Some(tracing_info) = self.tracing_infos.get_mut(key) {
-            tracing_info.done();
-        } else {
-            tracing::warn!("done failed, key: {} not found", key);
+    // set before the node is marked done or failed
+    pub(crate) fn record_history(&mut self, key: &FastStr, history: Vec<AttemptInfo>) {
+        if let Some(tracing_info) = self.get_or_add_tracing_info(key) {
+            tracing_info.history = history;
         }
     }
 
-    pub(crate) fn get_tracing_info(&self, key: &'static str) -> anyhow::Result<&TracingInfo> {
+    pub(crate) fn fail(&mut self, key: &FastStr, attempts: u32, error: String) {
+        self.counts.in_progress = self.counts.in_progress.saturating_sub(1);
+        self.counts.failed += 1;
+        let now = self.clock.now_micros();
+        if let Some(tracing_info) = self.get_or_add_tracing_info(key) {
+            tracing_info.fail(now, attempts, error);
+        }
+    }
+
+    pub(crate) fn cancel(&mut self, key: &FastStr, started: bool) {
+        if started {
+            self.counts.in_progress = self.counts.in_progress.saturating_sub(1);
+        }
+        self.counts.cancelled += 1;
+        let now = self.clock.now_micros();
+        if let Some(tracing_info) = self.get_or_add_tracing_info(key) {
+            tracing_info.cancel(now);
+        }
+    }
+
+    // fallback for a key that was never registered, e.g. an exector added after the manager was built.
+    // None when tracing is off
+    fn get_or_add_tracing_info(&mut self, key: &FastStr) -> Option<&mut TracingInfo> {
+        if !self.enabled {
+            return None;
+        }
+        Some(self.tracing_infos.entry(key.clone()).or_insert_with(|| {
+            tracing::warn!("key: {} not found in tracing_infos, add it", key);
+            TracingInfo::new()
+        }))
+    }
+
+    pub(crate) fn get_tracing_info(&self, key: &str) -> anyhow::Result<&TracingInfo> {
         self.tracing_infos
             .get(key)
             .ok_or_else(|| anyhow::anyhow!("not found {} in tracing_infos", key))
     }
-}
\ No newline at end of file
+
+    // every node ordered by name
+    pub(crate) fn snapshot(&self) -> Vec<NodeTiming> {
+        let mut timings: Vec<_> = self
+            .tracing_infos
+            .iter()
+            .map(|(name, tracing_info)| NodeTiming::new(name.clone(), tracing_info))
+            .collect();
+        timings.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        timings
+    }
+}
+
+// a copy of one node's tracing info, timestamps are in micros and 0 when not reached yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeTiming {
+    pub name: FastStr,
+    pub status: Status,
+    pub ready_micros: i64,
+    pub start_micros: i64,
+    pub end_micros: i64,
+    pub attempts: u32,
+    // skipped with the result of a previous run
+    pub cached: bool,
+    // set with `Manager::set_node_meta`, ordered by key
+    pub meta: BTreeMap<FastStr, String>,
+    pub history: Vec<AttemptInfo>,
+}
+
+impl NodeTiming {
+    pub(crate) fn new(name: FastStr, tracing_info: &TracingInfo) -> Self {
+        Self {
+            name,
+            status: tracing_info.status,
+            ready_micros: tracing_info.ready_time,
+            start_micros: tracing_info.start_time,
+            end_micros: tracing_info.end_time,
+            attempts: tracing_info.attempts,
+            cached: tracing_info.cached,
+            meta: BTreeMap::new(),
+            history: tracing_info.history.clone(),
+        }
+    }
+
+    // time spent ready but waiting for a concurrency slot, only for started nodes
+    pub fn wait(&self) -> Option<Duration> {
+        match self.status {
+            Status::NotStarted | Status::Skipped | Status::Cancelled => None,
+            _ => Some(Duration::from_micros(
+                (self.start_micros - self.ready_micros).max(0) as u64,
+            )),
+        }
+    }
+
+    // only finished nodes have a duration
+    pub fn duration(&self) -> Option<Duration> {
+        match self.status {
+            Status::Done | Status::Failed => Some(Duration::from_micros(
+                (self.end_micros - self.start_micros).max(0) as u64,
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unregistered_key_is_added_on_first_event() {
+        let mut tracing = TracingInfoManager::new();
+        let key = FastStr::from_static_str("late");
+        assert!(tracing.get_tracing_info(&key).is_err());
+
+        tracing.start(&key);
+        assert_eq!(
+            tracing.get_tracing_info(&key).unwrap().status,
+            Status::Doing
+        );
+        tracing.done(&key, 1);
+        let tracing_info = tracing.get_tracing_info(&key).unwrap();
+        assert_eq!(tracing_info.status, Status::Done);
+        assert!(tracing_info.end_time >= tracing_info.start_time);
+        assert_eq!(tracing.counts().done, 1);
+
+        // off, only the counts are kept
+        tracing.set_enabled(false, std::iter::empty());
+        tracing.start(&key);
+        assert!(tracing.get_tracing_info(&key).is_err());
+    }
+}
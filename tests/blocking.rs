@@ -0,0 +1,29 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use common::*;
+use cycle_loader::manager::Manager;
+
+#[test]
+fn run_blocking_without_runtime() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![count_exector("a", &count), count_exector("b", &count)]);
+    manager.add_dep("b", "a");
+
+    manager.run_blocking().unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+#[should_panic(expected = "inside a tokio runtime")]
+async fn run_blocking_inside_runtime_panics() {
+    let mut manager = Manager::new(1000);
+    let _ = manager.run_blocking();
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use common::*;
+use cycle_loader::{builder::ManagerBuilder, manager::Manager};
+
+#[tokio::test]
+async fn builder_builds_a_validated_manager() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::builder(1000)
+        .executor(count_exector("a", &count))
+        .executor(count_exector("b", &count))
+        .executor(count_exector("c", &count))
+        .dep("b", "a")
+        .edge("b", "c")
+        .build()
+        .unwrap();
+
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn builder_rejects_invalid_graphs() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let err = ManagerBuilder::new(1000)
+        .executor(count_exector("a", &count))
+        .executor(count_exector("a", &count))
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "exector name repeat: a");
+
+    let err = ManagerBuilder::new(1000)
+        .executor(count_exector("a", &count))
+        .dep("a", "missing")
+        .build()
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("missing -> a"));
+
+    let err = ManagerBuilder::new(1000)
+        .executor(count_exector("root", &count))
+        .executor(count_exector("a", &count))
+        .executor(count_exector("b", &count))
+        .dep("a", "root")
+        .dep("b", "a")
+        .dep("a", "b")
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "find cycle: a -> b -> a");
+}
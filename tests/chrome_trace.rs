@@ -0,0 +1,83 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        Arc,
+    },
+    time::Duration,
+};
+
+use common::*;
+use cycle_loader::manager::Manager;
+use serde_json::Value;
+
+fn events(manager: &Manager) -> Vec<Value> {
+    match serde_json::from_str(&manager.to_chrome_trace()).unwrap() {
+        Value::Array(events) => events,
+        other => panic!("trace should be an array, got {}", other),
+    }
+}
+
+fn event<'a>(events: &'a [Value], name: &str) -> &'a Value {
+    events.iter().find(|event| event["name"] == name).unwrap()
+}
+
+#[tokio::test]
+async fn chrome_trace_has_a_complete_event_per_node() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    assert_eq!(manager.to_chrome_trace(), "[]");
+    for name in ["load", "transform", "audit", "quote\"d"] {
+        manager.add_exector(count_exector(name, &count));
+    }
+    manager.add_dep("transform", "load");
+    manager.run().await.unwrap();
+
+    let events = events(&manager);
+    assert_eq!(events.len(), 4);
+    for event in &events {
+        assert_eq!(event["ph"], "X");
+        assert_eq!(event["pid"], 1);
+        assert!(event["ts"].as_i64().unwrap() > 0);
+        assert!(event["dur"].as_i64().unwrap() >= 0);
+        assert!(event["tid"].is_u64());
+    }
+    assert!(events.iter().any(|event| event["name"] == "quote\"d"));
+
+    let load = event(&events, "load");
+    let transform = event(&events, "transform");
+    assert!(
+        transform["ts"].as_i64().unwrap()
+            >= load["ts"].as_i64().unwrap() + load["dur"].as_i64().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn chrome_trace_keeps_running_nodes_open() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let finished = Arc::new(AtomicBool::new(false));
+    let mut manager = Manager::new(50);
+    manager.add_exector(count_exector("load", &count));
+    manager.add_exector(Box::new(SleepExecutor {
+        name: "slow",
+        sleep: Duration::from_secs(10),
+        finished,
+    }));
+    manager.add_exector(Box::new(SleepExecutor {
+        name: "fast",
+        sleep: Duration::from_millis(1),
+        finished: Arc::new(AtomicBool::new(false)),
+    }));
+    manager.add_dep("next", "slow");
+    manager.add_exector(count_exector("next", &count));
+    assert!(manager.run().await.is_err());
+
+    let events = events(&manager);
+    assert_eq!(events.len(), 3);
+    let slow = event(&events, "slow");
+    assert_eq!(slow["ph"], "B");
+    assert!(slow.get("dur").is_none());
+    assert_eq!(event(&events, "load")["ph"], "X");
+    assert_ne!(slow["tid"], event(&events, "fast")["tid"]);
+}
@@ -0,0 +1,140 @@
+#![allow(dead_code)]
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use cycle_loader::{
+    context::Context,
+    exector::{ExectorResult, Executor},
+};
+use faststr::FastStr;
+
+pub struct CountExecutor {
+    pub name: &'static str,
+    pub count: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Executor for CountExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.into()
+    }
+}
+
+pub fn count_exector(name: &'static str, count: &Arc<AtomicUsize>) -> Box<dyn Executor> {
+    Box::new(CountExecutor {
+        name,
+        count: count.clone(),
+    })
+}
+
+pub struct SleepExecutor {
+    pub name: &'static str,
+    pub sleep: Duration,
+    pub finished: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl Executor for SleepExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        tokio::time::sleep(self.sleep).await;
+        self.finished.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.into()
+    }
+}
+
+pub struct FlakyExecutor {
+    pub name: &'static str,
+    pub fail_times: usize,
+    pub attempts: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Executor for FlakyExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        if self.attempts.fetch_add(1, Ordering::SeqCst) < self.fail_times {
+            return Err(anyhow::anyhow!("flaky failure").into());
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.into()
+    }
+}
+
+pub struct FailExecutor {
+    pub name: &'static str,
+}
+
+#[async_trait]
+impl Executor for FailExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        Err(anyhow::anyhow!("{} failed", self.name).into())
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.into()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Concurrency {
+    pub in_flight: Arc<AtomicUsize>,
+    pub peak: Arc<AtomicUsize>,
+}
+
+pub struct PeakExecutor {
+    pub name: &'static str,
+    pub concurrency: Concurrency,
+}
+
+#[async_trait]
+impl Executor for PeakExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        let in_flight = self.concurrency.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.concurrency.peak.fetch_max(in_flight, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        self.concurrency.in_flight.fetch_sub(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.into()
+    }
+}
+
+// records the order exectors start in
+pub struct LogExecutor {
+    pub name: &'static str,
+    pub log: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[async_trait]
+impl Executor for LogExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        self.log.lock().unwrap().push(self.name);
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.into()
+    }
+}
+
+pub const LEAF_NAMES: [&str; 10] = ["n0", "n1", "n2", "n3", "n4", "n5", "n6", "n7", "n8", "n9"];
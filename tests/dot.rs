@@ -0,0 +1,70 @@
+mod common;
+
+use std::sync::{atomic::AtomicUsize, Arc};
+
+use common::*;
+use cycle_loader::manager::Manager;
+
+fn dot_manager(count: &Arc<AtomicUsize>) -> Manager {
+    let mut manager = Manager::new(1000);
+    for name in ["load", "transform", "store", "audit"] {
+        manager.add_exector(count_exector(name, count));
+    }
+    manager.add_dep("transform", "load");
+    manager.add_deps("store", vec!["transform", "load"]);
+    manager
+}
+
+#[test]
+fn to_dot_contains_every_edge() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let manager = dot_manager(&count);
+
+    let dot = manager.to_dot();
+    assert!(dot.starts_with("digraph cycle_loader {\n"));
+    assert!(dot.ends_with('}'));
+    for node in ["load", "transform", "store", "audit"] {
+        assert!(dot.contains(&format!("    \"{}\" [label=\"{}\"];\n", node, node)));
+    }
+    for (from, to) in [
+        ("load", "transform"),
+        ("transform", "store"),
+        ("load", "store"),
+    ] {
+        assert!(dot.contains(&format!("    \"{}\" -> \"{}\";\n", from, to)));
+    }
+    assert_eq!(dot.matches("->").count(), 3);
+}
+
+#[tokio::test]
+async fn to_dot_with_status_colors_nodes() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = dot_manager(&count);
+    assert!(manager.to_dot_with_status().contains("fillcolor=white"));
+
+    manager.run().await.unwrap();
+    let dot = manager.to_dot_with_status();
+    assert_eq!(dot.matches("fillcolor=green").count(), 4);
+}
+
+#[tokio::test]
+async fn node_meta_shows_in_dot_and_snapshot() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = dot_manager(&count);
+    manager.set_node_meta("load", "team", "ingest");
+    manager.set_node_meta("load", "cost_center", "42");
+
+    assert!(manager
+        .to_dot()
+        .contains("    \"load\" [label=\"load\", \"cost_center\"=\"42\", \"team\"=\"ingest\"];\n"));
+
+    manager.run().await.unwrap();
+    let load = manager.node_timing("load").unwrap();
+    assert_eq!(load.meta.get("team").map(String::as_str), Some("ingest"));
+    assert_eq!(load.meta.len(), 2);
+    assert!(manager
+        .tracing_snapshot()
+        .iter()
+        .filter(|timing| timing.name != "load")
+        .all(|timing| timing.meta.is_empty()));
+}
@@ -0,0 +1,73 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use cycle_loader::{
+    exector::{fn_executor, fn_once_executor},
+    manager::{FailureMode, Manager, RunError},
+    report::Outcome,
+};
+
+#[tokio::test]
+async fn closures_run_as_exectors() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    for name in ["load", "store"] {
+        let log = log.clone();
+        manager.add_exector(fn_executor(name, move || {
+            let log = log.clone();
+            async move {
+                log.lock().unwrap().push(name);
+                Ok(())
+            }
+        }));
+    }
+    let once_log = log.clone();
+    manager.add_exector(fn_once_executor("notify", move || async move {
+        once_log.lock().unwrap().push("notify");
+        Ok(())
+    }));
+    manager.add_dep("store", "load");
+    manager.add_dep("notify", "store");
+
+    manager.run().await.unwrap();
+    assert_eq!(*log.lock().unwrap(), vec!["load", "store", "notify"]);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.failed(), vec!["notify"]);
+    assert_eq!(log.lock().unwrap().len(), 5);
+}
+
+#[derive(Debug)]
+struct LoadError(u32);
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "load failed with code {}", self.0)
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+#[tokio::test]
+async fn exectors_return_their_own_error_type() {
+    let mut manager = Manager::new(1000);
+    manager.add_exector(fn_executor("load", || async { Err(LoadError(7).into()) }));
+    manager.set_failure_mode(FailureMode::StopOnError);
+
+    let err = manager.run().await.unwrap_err();
+    assert_eq!(err.to_string(), "exector load failed");
+    let RunError::NodeFailed { name, source, .. } = err else {
+        panic!("load should fail the run");
+    };
+    assert_eq!(name, "load");
+    assert!(matches!(source.downcast_ref(), Some(LoadError(7))));
+
+    manager.set_failure_mode(FailureMode::ContinueOnError);
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(
+        report.outcome("load"),
+        Some(&Outcome::Failed("load failed with code 7".to_string()))
+    );
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        Arc,
+    },
+    time::Duration,
+};
+
+use common::*;
+use cycle_loader::manager::Manager;
+
+#[tokio::test]
+async fn run_handle_probes_progress() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("a", &count),
+        Box::new(SleepExecutor {
+            name: "slow",
+            sleep: Duration::from_millis(100),
+            finished: Arc::new(AtomicBool::new(false)),
+        }),
+        Box::new(FailExecutor { name: "c" }),
+    ]);
+    manager.add_dep("slow", "a");
+
+    let handle = manager.spawn_run();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!handle.is_finished());
+    assert_eq!(handle.completed_count(), 2);
+    assert_eq!(handle.in_flight_count(), 1);
+    let last = handle.last_completion_time().unwrap();
+
+    let (manager, res) = handle.join().await;
+    assert_eq!(res.unwrap().failed(), vec!["c"]);
+    assert!(manager.node_timing("slow").unwrap().end_micros >= last);
+}
@@ -0,0 +1,2188 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use common::*;
+use cycle_loader::{
+    context::Context,
+    exector::{fn_executor, stop_branch, ExectorResult, Executor},
+    manager::{
+        ConditionErrorMode, DepKind, FailureMode, GraphStats, Manager, NodeView, Resources,
+        RunError,
+    },
+    progress::ProgressEvent,
+    report::Outcome,
+    retry::Backoff,
+    tracing_info::Status,
+};
+use faststr::FastStr;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn run_twice_executes_every_node_each_time() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("a", &count),
+        count_exector("b", &count),
+        count_exector("c", &count),
+        count_exector("d", &count),
+    ]);
+    manager.add_dep("b", "a");
+    manager.add_dep("c", "b");
+
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 4);
+
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 8);
+}
+
+#[tokio::test]
+async fn run_timeout_aborts_spawned_exectors() {
+    let finished = Arc::new(AtomicBool::new(false));
+    let mut manager = Manager::new(50);
+    manager.add_exector(Box::new(SleepExecutor {
+        name: "slow",
+        sleep: Duration::from_millis(200),
+        finished: finished.clone(),
+    }));
+    let count = Arc::new(AtomicUsize::new(0));
+    manager.add_exectors(vec![
+        count_exector("fast", &count),
+        count_exector("next", &count),
+    ]);
+    manager.add_dep("next", "slow");
+
+    let err = manager.run().await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "run timeout after 50ms, running: [slow], not started: [next], finished: 1"
+    );
+    assert!(matches!(
+        err,
+        RunError::Timeout {
+            elapsed_ms: 50,
+            finished: 1,
+            ..
+        }
+    ));
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!finished.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn node_timeout_fails_only_that_node() {
+    let slow_finished = Arc::new(AtomicBool::new(false));
+    let fast_finished = Arc::new(AtomicBool::new(false));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(SleepExecutor {
+            name: "slow",
+            sleep: Duration::from_millis(300),
+            finished: slow_finished.clone(),
+        }),
+        Box::new(SleepExecutor {
+            name: "fast",
+            sleep: Duration::from_millis(100),
+            finished: fast_finished.clone(),
+        }),
+    ]);
+    manager.set_node_timeout("slow", 50);
+
+    manager.run().await.unwrap();
+    assert!(fast_finished.load(Ordering::SeqCst));
+    assert!(!slow_finished.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn budget_timeouts_fail_the_slow_node() {
+    let slow_finished = Arc::new(AtomicBool::new(false));
+    let mut manager = Manager::new(200);
+    manager.add_exectors(vec![
+        fn_executor("fetch", || async { Ok(()) }),
+        Box::new(SleepExecutor {
+            name: "slow",
+            sleep: Duration::from_millis(170),
+            finished: slow_finished.clone(),
+        }),
+    ]);
+    manager.add_edge("fetch", "slow");
+    manager.set_expected_duration("fetch", Duration::from_millis(10));
+    manager.set_expected_duration("slow", Duration::from_millis(30));
+
+    // the flat limit lets the slow exector through
+    assert!(manager.run_with_report().await.unwrap().is_success());
+    assert!(slow_finished.load(Ordering::SeqCst));
+
+    // slow gets 150ms of the 200ms
+    slow_finished.store(false, Ordering::SeqCst);
+    manager.set_budget_timeouts(true);
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.failed(), vec!["slow"]);
+    assert!(report
+        .first_error
+        .unwrap()
+        .1
+        .contains("time limit is 150 ms"));
+    assert!(!slow_finished.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn retry_until_exector_succeeds() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(FlakyExecutor {
+            name: "flaky",
+            fail_times: 2,
+            attempts: attempts.clone(),
+        }),
+        count_exector("next", &count),
+    ]);
+    manager.add_dep("next", "flaky");
+    manager.set_retry("flaky", 5, Backoff::Exponential(Duration::from_millis(5)));
+
+    manager.run().await.unwrap();
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn report_keeps_every_attempt() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(FlakyExecutor {
+            name: "flaky",
+            fail_times: 2,
+            attempts: Arc::new(AtomicUsize::new(0)),
+        }),
+        count_exector("steady", &count),
+    ]);
+    manager.set_retry("flaky", 5, Backoff::Fixed(Duration::from_millis(10)));
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.retried(), vec!["flaky"]);
+    let attempts = &report.attempts["flaky"];
+    let errors: Vec<_> = attempts
+        .iter()
+        .map(|attempt| attempt.error.as_deref())
+        .collect();
+    assert_eq!(
+        errors,
+        vec![Some("flaky failure"), Some("flaky failure"), None]
+    );
+    // the node spans every attempt and the backoff between them
+    let timing = manager.node_timing("flaky").unwrap();
+    assert_eq!(timing.history, *attempts);
+    assert!(attempts[0].start_micros >= timing.start_micros);
+    assert!(attempts[2].end_micros <= timing.end_micros);
+    assert!(timing.duration().unwrap() >= Duration::from_millis(20));
+    assert_eq!(report.attempts["steady"].len(), 1);
+}
+
+#[tokio::test]
+async fn reset_clears_the_last_run_only() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = diamond_manager(&count, Some("c"));
+    manager.run().await.unwrap();
+    assert_eq!(manager.run_counts().failed, 1);
+    manager.context().insert(7u64);
+
+    manager.reset();
+    assert!(manager
+        .tracing_snapshot()
+        .iter()
+        .all(|timing| timing.status == Status::NotStarted));
+    assert_eq!(manager.run_counts(), Default::default());
+    assert_eq!(manager.run_id(), "");
+    assert!(manager.context().get::<u64>().is_none());
+    assert_eq!(manager.deps("d"), vec!["b", "c"]);
+
+    manager.add_or_replace_exector(count_exector("c", &count));
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.is_success());
+    assert_eq!(count.load(Ordering::SeqCst), 3 + 4);
+}
+
+#[tokio::test]
+async fn run_with_report_records_every_outcome() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("a", &count),
+        Box::new(FailExecutor { name: "b" }),
+    ]);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert!(matches!(
+        report.outcome("a"),
+        Some(Outcome::Completed { start, end }) if start <= end
+    ));
+    assert!(matches!(
+        report.outcome("b"),
+        Some(Outcome::Failed(err)) if err.contains("b failed")
+    ));
+    assert_eq!(report.completed(), vec!["a"]);
+    assert_eq!(report.failed(), vec!["b"]);
+    assert!(!report.is_success());
+}
+
+#[tokio::test]
+async fn report_keeps_the_first_error() {
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        fn_executor("late", || async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err(anyhow::anyhow!("late failed").into())
+        }),
+        Box::new(FailExecutor { name: "early" }),
+    ]);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.failed(), vec!["early", "late"]);
+    let (name, error) = report.first_error.unwrap();
+    assert_eq!(name, "early");
+    assert!(error.contains("early failed"));
+
+    manager.remove_executor("early");
+    manager.remove_executor("late");
+    manager.add_exector(count_exector("ok", &Arc::new(AtomicUsize::new(0))));
+    assert!(manager
+        .run_with_report()
+        .await
+        .unwrap()
+        .first_error
+        .is_none());
+}
+
+fn failure_mode_manager(count: &Arc<AtomicUsize>, slow_finished: &Arc<AtomicBool>) -> Manager {
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(FailExecutor { name: "a" }),
+        count_exector("b", count),
+        Box::new(SleepExecutor {
+            name: "slow",
+            sleep: Duration::from_millis(200),
+            finished: slow_finished.clone(),
+        }),
+    ]);
+    manager.add_dep("b", "a");
+    manager
+}
+
+#[tokio::test]
+async fn continue_on_error_keeps_scheduling() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let slow_finished = Arc::new(AtomicBool::new(false));
+    let mut manager = failure_mode_manager(&count, &slow_finished);
+    manager.set_failure_mode(FailureMode::ContinueOnError);
+
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert!(slow_finished.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn stop_on_error_aborts_the_run() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let slow_finished = Arc::new(AtomicBool::new(false));
+    let mut manager = failure_mode_manager(&count, &slow_finished);
+    manager.set_failure_mode(FailureMode::StopOnError);
+
+    let err = anyhow::Error::from(manager.run().await.unwrap_err());
+    assert!(format!("{:?}", err).contains("a failed"));
+    assert!(matches!(err.downcast_ref(), Some(RunError::NodeFailed { name, .. }) if name == "a"));
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+    assert!(!slow_finished.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn skip_on_dep_failure_skips_downstream() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(FailExecutor { name: "a" }),
+        count_exector("b", &count),
+        count_exector("c", &count),
+        count_exector("d", &count),
+    ]);
+    manager.add_dep("b", "a");
+    manager.add_dep("c", "b");
+    manager.set_failure_mode(FailureMode::SkipOnDepFailure);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(report.failed(), vec!["a"]);
+    let mut skipped = report.skipped();
+    skipped.sort();
+    assert_eq!(skipped, vec!["b", "c"]);
+    assert!(matches!(
+        report.outcome("d"),
+        Some(Outcome::Completed { .. })
+    ));
+}
+
+#[tokio::test]
+async fn max_concurrency_caps_in_flight_exectors() {
+    let concurrency = Concurrency::default();
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    for name in LEAF_NAMES {
+        manager.add_exector(Box::new(PeakExecutor {
+            name,
+            concurrency: concurrency.clone(),
+        }));
+    }
+    manager.add_exector(count_exector("sink", &count));
+    manager.add_deps("sink", vec!["n0"]);
+    manager.set_max_concurrency(3);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.is_success());
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(concurrency.peak.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn concurrency_groups_are_capped_independently() {
+    let redis = Concurrency::default();
+    let postgres = Concurrency::default();
+    let mut manager = Manager::new(1000);
+    for (index, name) in LEAF_NAMES.into_iter().enumerate() {
+        let (group, concurrency) = if index % 2 == 0 {
+            ("redis", &redis)
+        } else {
+            ("postgres", &postgres)
+        };
+        manager.add_exector(Box::new(PeakExecutor {
+            name,
+            concurrency: concurrency.clone(),
+        }));
+        manager.set_concurrency_group(name, group);
+    }
+    manager.set_group_limit("redis", 2);
+    manager.set_group_limit("postgres", 1);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.is_success());
+    assert_eq!(redis.peak.load(Ordering::SeqCst), 2);
+    assert_eq!(postgres.peak.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn resource_budget_keeps_heavy_exectors_apart() {
+    let heavy = Concurrency::default();
+    let light = Concurrency::default();
+    let mut manager = Manager::new(1000);
+    let nodes = [
+        ("heavy_a", &heavy, 600),
+        ("heavy_b", &heavy, 600),
+        ("light_a", &light, 100),
+        ("light_b", &light, 100),
+        ("huge", &light, 5000),
+    ];
+    for (name, concurrency, memory) in nodes {
+        manager.add_exector(Box::new(PeakExecutor {
+            name,
+            concurrency: concurrency.clone(),
+        }));
+        manager.set_cost(
+            name,
+            Resources::new().with("memory_mb", memory).with("cpu", 1),
+        );
+    }
+    manager.set_resource_budget(Resources::new().with("memory_mb", 1000));
+
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.is_success());
+    assert_eq!(heavy.peak.load(Ordering::SeqCst), 1);
+    // the light ones fit next to a heavy one, cpu has no budget
+    assert_eq!(light.peak.load(Ordering::SeqCst), 2);
+}
+
+struct ProduceExecutor;
+
+#[async_trait]
+impl Executor for ProduceExecutor {
+    async fn execute(&self, ctx: &Context) -> ExectorResult {
+        ctx.insert(21u64);
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        FastStr::from_static_str("produce")
+    }
+}
+
+struct ConsumeExecutor;
+
+#[async_trait]
+impl Executor for ConsumeExecutor {
+    async fn execute(&self, ctx: &Context) -> ExectorResult {
+        let input = ctx
+            .get::<u64>()
+            .ok_or_else(|| anyhow::anyhow!("produce output not found"))?;
+        ctx.insert(format!("answer is {}", *input * 2));
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        FastStr::from_static_str("consume")
+    }
+}
+
+#[tokio::test]
+async fn context_passes_outputs_downstream() {
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![Box::new(ProduceExecutor), Box::new(ConsumeExecutor)]);
+    manager.add_dep("consume", "produce");
+
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.is_success());
+    assert_eq!(
+        manager
+            .context()
+            .get::<String>()
+            .as_deref()
+            .map(String::as_str),
+        Some("answer is 42")
+    );
+}
+
+#[tokio::test]
+async fn entry_nodes_override_start_nodes() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("a", &count),
+        count_exector("b", &count),
+        count_exector("stray", &count),
+    ]);
+    manager.add_dep("b", "a");
+    manager.set_entry_nodes(vec!["a"]);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert_eq!(report.not_started(), vec!["stray"]);
+}
+
+#[tokio::test]
+async fn waiting_on_an_unrun_exector_stalls_the_run() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("a", &count),
+        count_exector("b", &count),
+        count_exector("c", &count),
+        count_exector("stray", &count),
+    ]);
+    manager.add_dep("b", "a");
+    manager.add_dep("b", "stray");
+    manager.add_dep("c", "b");
+    manager.set_entry_nodes(vec!["a"]);
+
+    let err = manager.run().await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "run stalled, stuck exectors: b waits for [stray], c waits for [b]"
+    );
+    assert_eq!(err.completed().len(), 1);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn ready_at_start_and_unsatisfied_deps() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("c", &count),
+        count_exector("a", &count),
+        count_exector("b", &count),
+        count_exector("join", &count),
+    ]);
+    manager.add_deps("join", vec!["a", "b", "c"]);
+    manager.add_dep("b", "a");
+    assert_eq!(manager.ready_at_start(), vec!["a", "c"]);
+
+    manager.set_entry_nodes(vec!["c"]);
+    assert_eq!(manager.ready_at_start(), vec!["c"]);
+    let err = manager.run().await.unwrap_err();
+    assert!(matches!(err, RunError::Stalled { .. }));
+    assert_eq!(manager.unsatisfied_deps("join").unwrap(), vec!["a", "b"]);
+    assert!(manager.unsatisfied_deps("b").unwrap().contains(&"a".into()));
+    assert!(manager.unsatisfied_deps("missing").is_err());
+
+    manager.set_entry_nodes(Vec::<&str>::new());
+    assert!(manager.ready_at_start().is_empty());
+    let err = manager.run().await.unwrap_err();
+    assert!(matches!(err, RunError::NoStartNodes));
+}
+
+#[tokio::test]
+async fn entry_node_with_deps_is_rejected() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![count_exector("a", &count), count_exector("b", &count)]);
+    manager.add_dep("b", "a");
+    manager.set_entry_nodes(vec!["b"]);
+
+    let err = manager.run().await.unwrap_err();
+    assert!(err.to_string().contains("entry node b has deps"));
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn validate_reports_unreachable_exectors() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("load", &count),
+        count_exector("transform", &count),
+        count_exector("store", &count),
+        count_exector("stray", &count),
+    ]);
+    manager.add_dep("transform", "load");
+    manager.add_dep("store", "transform");
+    manager.set_entry_nodes(vec!["load"]);
+
+    let err = manager.validate().unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(r#"unreachable exectors: ["stray"]"#));
+
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("load", &count),
+        count_exector("transform", &count),
+    ]);
+    manager.add_dep("transform", "load");
+    assert!(manager.validate().is_ok());
+}
+
+fn diamond_manager(count: &Arc<AtomicUsize>, fail_node: Option<&'static str>) -> Manager {
+    let mut manager = Manager::new(1000);
+    for name in ["a", "b", "c", "d"] {
+        if Some(name) == fail_node {
+            manager.add_exector(Box::new(FailExecutor { name }));
+        } else {
+            manager.add_exector(count_exector(name, count));
+        }
+    }
+    manager.add_deps("b", vec!["a"]);
+    manager.add_deps("c", vec!["a"]);
+    manager.add_deps("d", vec!["b", "c"]);
+    manager
+}
+
+#[test]
+fn validate_reports_cycle_path() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    for name in ["root", "a", "b", "c"] {
+        manager.add_exector(count_exector(name, &count));
+    }
+    manager.add_dep("a", "root");
+    manager.add_dep("b", "a");
+    manager.add_dep("c", "b");
+    manager.add_dep("a", "c");
+
+    let err = manager.validate().unwrap_err();
+    assert_eq!(err.to_string(), "find cycle: a -> b -> c -> a");
+    assert!(matches!(err, RunError::Cycle(path) if path == ["a", "b", "c", "a"]));
+}
+
+#[tokio::test]
+async fn graphs_without_start_nodes_say_why() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    let err = manager.run().await.unwrap_err();
+    assert!(matches!(err, RunError::EmptyGraph));
+    assert_eq!(err.to_string(), "no exectors to run");
+
+    // every exector has deps, so the cycle is named
+    for name in ["a", "b", "c"] {
+        manager.add_exector(count_exector(name, &count));
+    }
+    manager.add_dep("a", "b");
+    manager.add_dep("b", "a");
+    manager.add_dep("c", "b");
+    let err = manager.run().await.unwrap_err();
+    assert_eq!(err.to_string(), "find cycle: a -> b -> a");
+
+    assert!(manager.remove_edge("b", "a"));
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn diamond_is_not_a_cycle() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = diamond_manager(&count, None);
+
+    manager.validate().unwrap();
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn disjoint_subgraphs_are_not_a_cycle() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = diamond_manager(&count, None);
+    for name in ["x", "y", "z"] {
+        manager.add_exector(count_exector(name, &count));
+    }
+    manager.add_deps("z", vec!["x", "y"]);
+
+    manager.validate().unwrap();
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 7);
+}
+
+#[tokio::test]
+async fn diamond_continue_on_error_runs_every_node() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = diamond_manager(&count, Some("b"));
+    manager.set_failure_mode(FailureMode::ContinueOnError);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+    assert_eq!(report.failed(), vec!["b"]);
+    assert!(matches!(
+        report.outcome("d"),
+        Some(Outcome::Completed { .. })
+    ));
+}
+
+#[tokio::test]
+async fn diamond_stop_on_error_never_reaches_the_sink() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = diamond_manager(&count, Some("b"));
+    manager.set_failure_mode(FailureMode::StopOnError);
+
+    assert!(manager.run().await.is_err());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    // only a and maybe c ran
+    assert!(count.load(Ordering::SeqCst) <= 2);
+}
+
+#[test]
+fn validate_reports_dangling_edges() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("load", &count),
+        count_exector("store", &count),
+    ]);
+    manager.add_deps("store", vec!["load", "lod"]);
+
+    let err = manager.validate().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "edges reference unknown exectors: lod -> store"
+    );
+}
+
+struct NamedExecutor {
+    name: FastStr,
+    count: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Executor for NamedExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.clone()
+    }
+}
+
+#[tokio::test]
+async fn runtime_owned_names() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let names: Vec<String> = (0..3).map(|index| format!("step_{}", index)).collect();
+    let mut manager = Manager::new(1000);
+    for name in names.iter() {
+        manager.add_exector(Box::new(NamedExecutor {
+            name: FastStr::new(name),
+            count: count.clone(),
+        }));
+    }
+    for pair in names.windows(2) {
+        manager.add_dep(pair[1].clone(), pair[0].clone());
+    }
+
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.is_success());
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+    assert!(manager.to_dot().contains("\"step_0\" -> \"step_1\""));
+}
+
+#[tokio::test]
+async fn run_rejects_edges_to_unknown_exectors() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![count_exector("a", &count), count_exector("b", &count)]);
+    manager.add_dep("b", "a");
+    manager.add_edge("a", "missing");
+    manager.add_dep("b", "lost");
+
+    let err = manager.run().await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "edges reference unknown exectors: a -> missing, lost -> b"
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn plan_groups_exectors_into_waves() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = diamond_manager(&count, None);
+    manager.add_exector(count_exector("x", &count));
+    manager.add_exector(count_exector("e", &count));
+    manager.add_deps("e", vec!["d", "x"]);
+
+    let plan = manager.plan().unwrap();
+    assert_eq!(
+        plan,
+        vec![vec!["a", "x"], vec!["b", "c"], vec!["d"], vec!["e"]]
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+
+    manager.add_dep("a", "e");
+    assert!(manager.plan().is_err());
+}
+
+#[test]
+fn topo_order_is_deterministic() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = diamond_manager(&count, None);
+    manager.add_exector(count_exector("x", &count));
+    manager.add_dep("c", "x");
+
+    assert_eq!(manager.topo_order().unwrap(), vec!["a", "b", "x", "c", "d"]);
+
+    manager.add_dep("a", "d");
+    let err = manager.topo_order().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "find cycle, no valid order for: a, b, c, d"
+    );
+}
+
+#[tokio::test]
+async fn false_condition_skips_node_but_not_its_subtree() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("a", &count),
+        count_exector("x", &count),
+        count_exector("b", &count),
+        count_exector("c", &count),
+    ]);
+    manager.add_deps("b", vec!["a", "x"]);
+    manager.add_dep("c", "b");
+    manager.set_condition("a", Box::new(|_| false));
+    manager.set_failure_mode(FailureMode::SkipOnDepFailure);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+    assert_eq!(report.skipped(), vec!["a"]);
+    assert!(matches!(
+        report.outcome("c"),
+        Some(Outcome::Completed { .. })
+    ));
+}
+
+#[tokio::test]
+async fn condition_sees_upstream_outputs() {
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![Box::new(ProduceExecutor), Box::new(ConsumeExecutor)]);
+    manager.add_dep("consume", "produce");
+    manager.set_condition("consume", Box::new(|ctx| ctx.get::<u64>().is_some()));
+    manager.set_condition("produce", Box::new(|ctx| ctx.get::<u64>().is_none()));
+
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.is_success());
+    assert!(report.skipped().is_empty());
+    assert!(manager.context().get::<String>().is_some());
+}
+
+#[tokio::test]
+async fn async_conditions_skip_or_fail() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("flagged", &count),
+        count_exector("next", &count),
+        count_exector("broken", &count),
+    ]);
+    manager.add_dep("next", "flagged");
+    manager.set_async_condition(
+        "flagged",
+        Box::new(|_| {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                Ok(false)
+            })
+        }),
+    );
+    manager.set_async_condition(
+        "broken",
+        Box::new(|_| Box::pin(async { Err(anyhow::anyhow!("flag service down").into()) })),
+    );
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(report.skipped(), vec!["flagged"]);
+    assert!(matches!(
+        report.outcome("next"),
+        Some(Outcome::Completed { .. })
+    ));
+    let Some(Outcome::Failed(error)) = report.outcome("broken") else {
+        panic!("broken should fail");
+    };
+    assert_eq!(
+        error,
+        "condition of exector broken failed: flag service down"
+    );
+    assert!(manager.node_timing("flagged").unwrap().start_micros > 0);
+
+    manager.set_condition_error_mode(ConditionErrorMode::Skip);
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.skipped(), vec!["broken", "flagged"]);
+}
+
+#[tokio::test]
+async fn priorities_order_queued_exectors() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    for name in ["a", "b", "c"] {
+        manager.add_exector(Box::new(LogExecutor {
+            name,
+            log: log.clone(),
+        }));
+    }
+    manager.set_max_concurrency(1);
+    manager.set_priority("a", -1);
+    manager.set_priority("c", 10);
+
+    manager.run().await.unwrap();
+    assert_eq!(*log.lock().unwrap(), vec!["c", "b", "a"]);
+}
+
+#[tokio::test]
+async fn dropping_the_run_aborts_exectors() {
+    let finished = Arc::new(AtomicBool::new(false));
+    let mut manager = Manager::new(1000);
+    manager.add_exector(Box::new(SleepExecutor {
+        name: "slow",
+        sleep: Duration::from_millis(100),
+        finished: finished.clone(),
+    }));
+
+    assert!(
+        tokio::time::timeout(Duration::from_millis(20), manager.run())
+            .await
+            .is_err()
+    );
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!finished.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn run_until_uses_the_deadline() {
+    let finished = Arc::new(AtomicBool::new(false));
+    let mut manager = Manager::new(1000);
+    manager.add_exector(Box::new(SleepExecutor {
+        name: "slow",
+        sleep: Duration::from_millis(100),
+        finished: finished.clone(),
+    }));
+
+    let deadline = Instant::now() - Duration::from_millis(1);
+    let err = manager.run_until(deadline).await.unwrap_err();
+    assert!(matches!(err, RunError::Timeout { elapsed_ms: 0, .. }));
+    assert_eq!(manager.tracing_snapshot()[0].status, Status::NotStarted);
+
+    let deadline = Instant::now() + Duration::from_millis(20);
+    assert!(manager.run_until(deadline).await.is_err());
+    assert!(!finished.load(Ordering::SeqCst));
+
+    let deadline = Instant::now() + Duration::from_millis(500);
+    manager.run_until(deadline).await.unwrap();
+    assert!(finished.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn node_complete_hook_fires_for_every_node() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let outcomes = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = diamond_manager(&count, Some("c"));
+    let hook_outcomes = outcomes.clone();
+    manager.on_node_complete(Box::new(move |name, outcome| {
+        hook_outcomes
+            .lock()
+            .unwrap()
+            .push((name.to_string(), outcome.is_success()));
+    }));
+
+    manager.run().await.unwrap();
+    let mut outcomes = outcomes.lock().unwrap().clone();
+    outcomes.sort();
+    assert_eq!(
+        outcomes,
+        vec![
+            ("a".to_string(), true),
+            ("b".to_string(), true),
+            ("c".to_string(), false),
+            ("d".to_string(), true),
+        ]
+    );
+}
+
+struct DeclaredDepsExecutor {
+    name: &'static str,
+    deps: Vec<&'static str>,
+    log: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[async_trait]
+impl Executor for DeclaredDepsExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        self.log.lock().unwrap().push(self.name);
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.into()
+    }
+
+    fn deps(&self) -> Vec<FastStr> {
+        self.deps.iter().map(|dep| FastStr::from(*dep)).collect()
+    }
+}
+
+#[tokio::test]
+async fn declared_deps_merge_with_added_deps() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(LogExecutor {
+            name: "a",
+            log: log.clone(),
+        }),
+        Box::new(LogExecutor {
+            name: "b",
+            log: log.clone(),
+        }),
+        Box::new(DeclaredDepsExecutor {
+            name: "c",
+            deps: vec!["a"],
+            log: log.clone(),
+        }),
+    ]);
+    manager.add_dep("c", "b");
+    manager.add_dep("c", "a");
+
+    assert_eq!(manager.plan().unwrap(), vec![vec!["a", "b"], vec!["c"]]);
+    manager.run().await.unwrap();
+    assert_eq!(log.lock().unwrap().last(), Some(&"c"));
+}
+
+fn assert_edges_in_sync(manager: &Manager, names: &[&str]) {
+    for name in names {
+        for dep in manager.deps(name) {
+            assert!(manager.dependents(&dep).contains(&FastStr::new(name)));
+        }
+        for dependent in manager.dependents(name) {
+            assert!(manager.deps(&dependent).contains(&FastStr::new(name)));
+        }
+    }
+}
+
+#[tokio::test]
+async fn removing_edges_and_exectors_keeps_lists_in_sync() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = diamond_manager(&count, None);
+    let names = ["a", "b", "c", "d"];
+    manager.add_dep("d", "b");
+    assert_eq!(manager.deps("d"), vec!["b", "c"]);
+    assert_edges_in_sync(&manager, &names);
+
+    assert!(manager.remove_edge("b", "d"));
+    assert!(!manager.remove_edge("b", "d"));
+    assert_eq!(manager.deps("d"), vec!["c"]);
+    assert_eq!(manager.dependents("b"), Vec::<FastStr>::new());
+    assert_edges_in_sync(&manager, &names);
+
+    assert!(manager.remove_executor("c").is_some());
+    assert!(manager.remove_executor("c").is_none());
+    assert!(manager.deps("d").is_empty());
+    assert_eq!(manager.dependents("a"), vec!["b"]);
+    assert_edges_in_sync(&manager, &names);
+
+    manager.validate().unwrap();
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+#[should_panic(expected = "exector c exceeds the limit of 2 exectors")]
+fn limits_bound_exector_count() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.set_limits(2, 10);
+    for name in ["a", "b", "c"] {
+        manager.add_exector(count_exector(name, &count));
+    }
+}
+
+#[test]
+#[should_panic(expected = "exector b exceeds the limit of 1 exectors")]
+fn add_or_replace_panics_on_a_new_name_over_the_node_limit() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.set_limits(1, 10);
+    manager.add_exector(count_exector("a", &count));
+    // replacing stays possible at the limit
+    assert!(manager
+        .add_or_replace_exector(count_exector("a", &count))
+        .is_some());
+    manager.add_or_replace_exector(count_exector("b", &count));
+}
+
+#[test]
+#[should_panic(expected = "edge a -> d exceeds the limit of 2 edges")]
+fn limits_bound_edge_count() {
+    let mut manager = Manager::new(1000);
+    manager.set_limits(10, 2);
+    manager.add_edges("a", vec!["b", "c"]);
+    // duplicates and removed edges do not count
+    manager.add_edge("a", "b");
+    manager.remove_edge("a", "c");
+    manager.add_edge("a", "c");
+    manager.add_edge("a", "d");
+}
+
+#[test]
+#[should_panic(expected = "edge a -> c exceeds the fan out limit of 1")]
+fn limits_bound_fan_out() {
+    let mut manager = Manager::new(1000);
+    manager.set_max_fan_out(1);
+    manager.add_edges("b", vec!["c"]);
+    manager.add_edges("a", vec!["b", "c"]);
+}
+
+#[tokio::test]
+async fn declared_deps_over_the_limits_are_rejected() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let declaring = |name| {
+        Box::new(DeclaredDepsExecutor {
+            name,
+            deps: vec!["w", "v"],
+            log: log.clone(),
+        })
+    };
+    let mut manager = Manager::new(1000);
+    manager.set_limits(10, 0);
+    let err = manager.try_add_exector(declaring("a")).unwrap_err();
+    assert_eq!(err.to_string(), "edge w -> a exceeds the limit of 0 edges");
+    assert!(!manager.has_exector("a"));
+
+    // the deps together break the limit, none of them is added
+    manager.set_limits(10, 1);
+    let err = manager.try_add_exector(declaring("a")).unwrap_err();
+    assert_eq!(err.to_string(), "edge v -> a exceeds the limit of 1 edges");
+    assert!(!manager.has_exector("a"));
+    assert!(manager.deps("a").is_empty());
+
+    manager.set_limits(10, 2);
+    manager.try_add_exector(declaring("a")).unwrap();
+    assert_eq!(manager.deps("a"), vec!["v", "w"]);
+}
+
+#[tokio::test]
+async fn cancel_stops_the_run() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let finished = Arc::new(AtomicBool::new(false));
+    let mut manager = Manager::new(1000);
+    manager.add_exector(Box::new(SleepExecutor {
+        name: "slow",
+        sleep: Duration::from_millis(100),
+        finished: finished.clone(),
+    }));
+    manager.add_exector(Box::new(LogExecutor {
+        name: "next",
+        log: log.clone(),
+    }));
+    manager.add_dep("next", "slow");
+
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        cancel.cancel();
+    });
+    let err = manager.run_with_cancel(token).await.unwrap_err();
+    assert!(matches!(err, RunError::Cancelled { .. }));
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!finished.load(Ordering::SeqCst));
+    assert!(log.lock().unwrap().is_empty());
+}
+
+fn sleeper(name: &'static str, millis: u64, finished: &Arc<AtomicBool>) -> Box<SleepExecutor> {
+    Box::new(SleepExecutor {
+        name,
+        sleep: Duration::from_millis(millis),
+        finished: finished.clone(),
+    })
+}
+
+#[tokio::test]
+async fn branch_token_drops_only_the_exclusive_branch() {
+    let finished = Arc::new(AtomicBool::new(false));
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        sleeper("gate", 50, &Arc::new(AtomicBool::new(false))),
+        sleeper("shared", 80, &Arc::new(AtomicBool::new(false))),
+        count_exector("input", &count),
+        sleeper("target", 10, &finished),
+        count_exector("below", &count),
+        count_exector("other", &count),
+    ]);
+    manager.add_dep("input", "gate");
+    manager.add_deps("target", vec!["input", "shared"]);
+    manager.add_dep("below", "target");
+    manager.add_dep("other", "shared");
+
+    let token = manager.branch_token("target");
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        token.cancel();
+    });
+    let report = manager.run_with_report().await.unwrap();
+    // gate was running already and shared is needed by other
+    assert_eq!(report.cancelled(), vec!["below", "input", "target"]);
+    assert_eq!(report.completed(), vec!["gate", "other", "shared"]);
+    assert!(report.is_success());
+    assert_eq!(manager.run_counts().cancelled, 3);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    // the token is gone with its run
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.cancelled().is_empty());
+    assert!(finished.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn branch_token_aborts_a_running_target() {
+    let finished = Arc::new(AtomicBool::new(false));
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        sleeper("target", 300, &finished),
+        count_exector("pre", &count),
+        count_exector("other", &count),
+    ]);
+    manager.add_dep("other", "pre");
+    let outcomes = Arc::new(Mutex::new(Vec::new()));
+    let hook_outcomes = outcomes.clone();
+    manager.on_node_complete(Box::new(move |name, outcome| {
+        hook_outcomes
+            .lock()
+            .unwrap()
+            .push((name.to_string(), outcome));
+    }));
+    let mut progress = manager.progress_channel();
+
+    let token = manager.branch_token("target");
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        token.cancel();
+    });
+    let started = Instant::now();
+    let report = manager.run_with_report().await.unwrap();
+    assert!(started.elapsed() < Duration::from_millis(200));
+    assert_eq!(report.cancelled(), vec!["target"]);
+    assert_eq!(report.completed(), vec!["other", "pre"]);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!finished.load(Ordering::SeqCst));
+    // reported as cancelled, not as a success
+    let (_, outcome) = outcomes
+        .lock()
+        .unwrap()
+        .drain(..)
+        .find(|(name, _)| name == "target")
+        .unwrap();
+    assert!(outcome.cancelled && !outcome.is_success());
+    assert!(outcome.duration >= Duration::from_millis(15));
+    let mut events = Vec::new();
+    while let Ok(event) = progress.try_recv() {
+        events.push(event);
+    }
+    assert!(events.contains(&ProgressEvent::Cancelled("target".into())));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, ProgressEvent::Finished(name, _) if name == "target")));
+
+    // cancelled between runs, pre only feeds the target now
+    manager.add_dep("target", "pre");
+    manager.remove_edge("pre", "other");
+    manager.branch_token("target").cancel();
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.cancelled(), vec!["pre", "target"]);
+    assert_eq!(report.completed(), vec!["other"]);
+    let mut cancelled: Vec<_> = outcomes
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, outcome)| outcome.cancelled)
+        .map(|(name, outcome)| (name.clone(), outcome.is_success(), outcome.duration))
+        .collect();
+    cancelled.sort();
+    assert_eq!(
+        cancelled,
+        vec![
+            ("pre".to_string(), false, Duration::ZERO),
+            ("target".to_string(), false, Duration::ZERO)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn branch_token_is_spent_by_a_timed_out_run() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(50);
+    manager.add_exectors(vec![
+        sleeper("slow", 150, &Arc::new(AtomicBool::new(false))),
+        count_exector("target", &count),
+    ]);
+    manager.add_dep("target", "slow");
+
+    let token = manager.branch_token("target");
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        token.cancel();
+    });
+    assert!(matches!(manager.run().await, Err(RunError::Timeout { .. })));
+
+    // the next run does not cancel the target again
+    manager.set_timeout_ms(1000);
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.cancelled().is_empty());
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn try_add_and_replace_exectors() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let replaced = Arc::new(AtomicUsize::new(0));
+    let mut manager = diamond_manager(&count, None);
+
+    let err = manager
+        .try_add_exector(count_exector("b", &count))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "exector name repeat: b");
+
+    assert!(manager
+        .add_or_replace_exector(count_exector("b", &replaced))
+        .is_some());
+    assert!(manager
+        .add_or_replace_exector(count_exector("e", &replaced))
+        .is_none());
+    manager.add_dep("e", "d");
+    assert_eq!(manager.deps("b"), vec!["a"]);
+
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+    assert_eq!(replaced.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn overlapping_runs_are_rejected() {
+    let finished = Arc::new(AtomicBool::new(false));
+    let mut manager = Manager::new(1000);
+    manager.add_exector(Box::new(SleepExecutor {
+        name: "slow",
+        sleep: Duration::from_millis(50),
+        finished: finished.clone(),
+    }));
+    assert!(!manager.is_running());
+
+    // a dropped run clears the flag
+    let mut run = Box::pin(manager.run());
+    assert!(futures::poll!(&mut run).is_pending());
+    drop(run);
+    assert!(!manager.is_running());
+
+    // a run that never finished nor got dropped keeps it
+    let mut run = Box::pin(manager.run());
+    assert!(futures::poll!(&mut run).is_pending());
+    std::mem::forget(run);
+    assert!(manager.is_running());
+    let err = manager.run().await.unwrap_err();
+    assert_eq!(err.to_string(), "run already in progress");
+}
+
+// a -> b -> d, a -> c -> e, x -> d
+fn branchy_manager(log: &Arc<Mutex<Vec<&'static str>>>) -> Manager {
+    let mut manager = Manager::new(1000);
+    for name in ["a", "b", "c", "d", "e", "x"] {
+        manager.add_exector(Box::new(LogExecutor {
+            name,
+            log: log.clone(),
+        }));
+    }
+    manager.add_dep("b", "a");
+    manager.add_dep("c", "a");
+    manager.add_deps("d", vec!["b", "x"]);
+    manager.add_dep("e", "c");
+    manager
+}
+
+fn sorted_log(log: &Arc<Mutex<Vec<&'static str>>>) -> Vec<&'static str> {
+    let mut log = std::mem::take(&mut *log.lock().unwrap());
+    log.sort();
+    log
+}
+
+#[tokio::test]
+async fn run_target_runs_only_its_ancestors() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = branchy_manager(&log);
+
+    manager.run_target("d").await.unwrap();
+    assert_eq!(sorted_log(&log), vec!["a", "b", "d", "x"]);
+    assert_eq!(manager.tracing_snapshot()[2].status, Status::NotStarted);
+
+    manager.run_target("x").await.unwrap();
+    assert_eq!(sorted_log(&log), vec!["x"]);
+
+    manager.run_target("e").await.unwrap();
+    assert_eq!(sorted_log(&log), vec!["a", "c", "e"]);
+
+    let err = manager.run_target("missing").await.unwrap_err();
+    assert_eq!(err.to_string(), "target missing is not an exector");
+}
+
+#[tokio::test]
+async fn components_run_in_isolation() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    for name in ["load", "store", "ping", "report", "solo"] {
+        manager.add_exector(Box::new(LogExecutor {
+            name,
+            log: log.clone(),
+        }));
+    }
+    manager.add_dep("store", "load");
+    manager.add_dep("report", "ping");
+    manager.add_dep("report", "load");
+    manager.add_exector(Box::new(LogExecutor {
+        name: "other",
+        log: log.clone(),
+    }));
+    manager.add_dep("other", "solo");
+
+    assert_eq!(
+        manager.components(),
+        vec![
+            vec!["load", "ping", "report", "store"],
+            vec!["other", "solo"],
+        ]
+    );
+
+    manager.run_component(1).await.unwrap();
+    assert_eq!(sorted_log(&log), vec!["other", "solo"]);
+    manager.run_component(0).await.unwrap();
+    assert_eq!(sorted_log(&log), vec!["load", "ping", "report", "store"]);
+
+    let err = manager.run_component(2).await.unwrap_err();
+    assert_eq!(err.to_string(), "component 2 out of range");
+}
+
+#[tokio::test]
+async fn run_targets_share_ancestors() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = branchy_manager(&log);
+
+    manager.run_targets(vec!["b", "e"]).await.unwrap();
+    assert_eq!(sorted_log(&log), vec!["a", "b", "c", "e"]);
+
+    manager.run_targets(vec!["d", "b", "x"]).await.unwrap();
+    assert_eq!(sorted_log(&log), vec!["a", "b", "d", "x"]);
+
+    assert!(manager.run_targets(vec!["d", "missing"]).await.is_err());
+    assert!(log.lock().unwrap().is_empty());
+}
+
+struct CachedExecutor {
+    name: &'static str,
+    key: Arc<AtomicU64>,
+    count: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Executor for CachedExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.into()
+    }
+
+    fn cache_key(&self) -> Option<u64> {
+        Some(self.key.load(Ordering::SeqCst))
+    }
+}
+
+#[tokio::test]
+async fn cache_skips_unchanged_exectors() {
+    let keys: Vec<_> = (0..3).map(|_| Arc::new(AtomicU64::new(1))).collect();
+    let counts: Vec<_> = (0..3).map(|_| Arc::new(AtomicUsize::new(0))).collect();
+    let mut manager = Manager::new(1000);
+    for (index, name) in ["a", "b", "c"].into_iter().enumerate() {
+        manager.add_exector(Box::new(CachedExecutor {
+            name,
+            key: keys[index].clone(),
+            count: counts[index].clone(),
+        }));
+    }
+    manager.add_dep("b", "a");
+    let runs = |counts: &[Arc<AtomicUsize>]| {
+        counts
+            .iter()
+            .map(|count| count.load(Ordering::SeqCst))
+            .collect::<Vec<_>>()
+    };
+
+    manager.run().await.unwrap();
+    assert_eq!(runs(&counts), vec![1, 1, 1]);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(runs(&counts), vec![1, 1, 1]);
+    assert_eq!(report.skipped(), vec!["a", "b", "c"]);
+    assert!(manager
+        .tracing_snapshot()
+        .iter()
+        .all(|timing| timing.cached));
+
+    // b keeps its key but a ran again, so b runs too
+    keys[0].store(2, Ordering::SeqCst);
+    manager.run().await.unwrap();
+    assert_eq!(runs(&counts), vec![2, 2, 1]);
+
+    manager.clear_cache();
+    manager.run().await.unwrap();
+    assert_eq!(runs(&counts), vec![3, 3, 2]);
+}
+
+#[tokio::test]
+async fn barrier_separates_phases() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    for name in ["extract_a", "extract_b", "load_a", "load_b"] {
+        manager.add_exector(Box::new(LogExecutor {
+            name,
+            log: log.clone(),
+        }));
+    }
+    manager.add_barrier_between(
+        "extracted",
+        vec!["extract_a", "extract_b"],
+        vec!["load_a", "load_b"],
+    );
+    assert_eq!(manager.deps("extracted"), vec!["extract_a", "extract_b"]);
+    assert_eq!(manager.dependents("extracted"), vec!["load_a", "load_b"]);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.is_success());
+    let log = log.lock().unwrap();
+    assert_eq!(log.len(), 4);
+    assert!(log[..2].iter().all(|name| name.starts_with("extract")));
+
+    // the barrier is an ordinary node for validation
+    manager.add_dep("extract_a", "load_b");
+    assert!(manager.validate().is_err());
+}
+
+#[tokio::test]
+async fn inline_exectors_run_on_the_run_loop() {
+    let spawned = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    for name in ["a", "b", "c"] {
+        let spawned = spawned.clone();
+        manager.add_exector(fn_executor(name, move || {
+            let spawned = spawned.clone();
+            async move {
+                // the test body itself is not a task
+                let in_task = tokio::task::try_id().is_some();
+                spawned.lock().unwrap().push((name, in_task));
+                Ok(())
+            }
+        }));
+    }
+    manager.add_exector(fn_executor("boom", || async { panic!("bad input") }));
+    manager.add_deps("c", vec!["a", "b"]);
+    manager.add_dep("boom", "c");
+    manager.set_inline("a");
+    manager.set_inline("c");
+    manager.set_inline("boom");
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.completed(), vec!["a", "b", "c"]);
+    assert_eq!(
+        report.outcome("boom"),
+        Some(&Outcome::Failed(
+            "exector boom panicked: bad input".to_string()
+        ))
+    );
+    let mut spawned = spawned.lock().unwrap().clone();
+    spawned.sort_unstable();
+    assert_eq!(spawned, vec![("a", false), ("b", true), ("c", false)]);
+}
+
+#[tokio::test]
+async fn panicked_exector_fails_its_node() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exector(fn_executor("boom", || async { panic!("bad input") }));
+    manager.add_exectors(vec![
+        count_exector("after", &count),
+        count_exector("other", &count),
+    ]);
+    manager.add_dep("after", "boom");
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.failed(), vec!["boom"]);
+    assert_eq!(
+        report.outcome("boom"),
+        Some(&Outcome::Failed(
+            "exector boom panicked: bad input".to_string()
+        ))
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    manager.set_failure_mode(FailureMode::StopOnError);
+    let err = manager.run().await.unwrap_err();
+    assert!(matches!(err, RunError::NodeFailed { name, .. } if name == "boom"));
+}
+
+#[tokio::test]
+async fn tenants_take_turns_at_free_slots() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    manager.set_max_concurrency(1);
+    for name in ["a0", "a1", "a2", "a3", "a4", "b0", "b1"] {
+        manager.add_exector(Box::new(LogExecutor {
+            name,
+            log: log.clone(),
+        }));
+        manager.set_tenant(name, &name[..1]);
+    }
+    // priority orders a tenant's own exectors only
+    manager.set_priority("a4", 10);
+
+    manager.run().await.unwrap();
+    let log = log.lock().unwrap();
+    let tenants: String = log.iter().map(|name| &name[..1]).collect();
+    assert_eq!(tenants, "ababaaa");
+    assert_eq!(log[0], "a4");
+}
+
+struct ContextExecutor {
+    name: &'static str,
+    f: fn(&Context) -> ExectorResult,
+}
+
+#[async_trait]
+impl Executor for ContextExecutor {
+    async fn execute(&self, ctx: &Context) -> ExectorResult {
+        (self.f)(ctx)
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.into()
+    }
+}
+
+struct Secret;
+
+#[tokio::test]
+async fn context_scope_hides_values_but_keeps_outputs() {
+    let mut manager = Manager::new(1000);
+    manager.add_exector(Box::new(ContextExecutor {
+        name: "login",
+        f: |ctx| {
+            ctx.insert(Secret);
+            ctx.insert(21u64);
+            Ok(())
+        },
+    }));
+    manager.add_exector(Box::new(ContextExecutor {
+        name: "report",
+        f: |ctx| {
+            if ctx.contains::<Secret>() {
+                return Err("report can read the secret".into());
+            }
+            ctx.insert(format!("answer is {}", *ctx.get::<u64>().unwrap() * 2));
+            Ok(())
+        },
+    }));
+    manager.add_exector(Box::new(ContextExecutor {
+        name: "publish",
+        f: |ctx| {
+            ctx.get::<String>().ok_or("report output not found")?;
+            Ok(())
+        },
+    }));
+    manager.add_dep("report", "login");
+    manager.add_dep("publish", "report");
+    manager.set_context_scope("report", Box::new(|ctx| ctx.without::<Secret>()));
+
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.is_success());
+    let ctx = manager.context();
+    assert!(ctx.contains::<Secret>());
+    assert_eq!(*ctx.get::<String>().unwrap(), "answer is 42");
+}
+
+#[derive(Debug, PartialEq)]
+struct Foo(u32);
+
+// like `ContextExecutor`, but sleeps first
+struct SlowContextExecutor {
+    name: &'static str,
+    sleep: Duration,
+    f: fn(&Context) -> ExectorResult,
+}
+
+#[async_trait]
+impl Executor for SlowContextExecutor {
+    async fn execute(&self, ctx: &Context) -> ExectorResult {
+        tokio::time::sleep(self.sleep).await;
+        (self.f)(ctx)
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.into()
+    }
+}
+
+#[tokio::test]
+async fn context_scope_keeps_values_written_on_a_sibling_branch() {
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(ContextExecutor {
+            name: "seed",
+            f: |ctx| {
+                ctx.insert(Foo(1));
+                Ok(())
+            },
+        }),
+        Box::new(ContextExecutor {
+            name: "writer",
+            f: |ctx| {
+                ctx.insert(Foo(2));
+                Ok(())
+            },
+        }),
+        Box::new(SlowContextExecutor {
+            name: "scoped",
+            sleep: Duration::from_millis(30),
+            f: |ctx| {
+                ctx.insert(String::from("scoped output"));
+                Ok(())
+            },
+        }),
+    ]);
+    manager.add_dep("writer", "seed");
+    manager.add_dep("scoped", "seed");
+    manager.set_context_scope("scoped", Box::new(|ctx| ctx.without::<Secret>()));
+
+    manager.run().await.unwrap();
+    let ctx = manager.context();
+    // the scope saw Foo(1) but never touched it
+    assert_eq!(*ctx.get::<Foo>().unwrap(), Foo(2));
+    assert_eq!(*ctx.get::<String>().unwrap(), "scoped output");
+}
+
+#[tokio::test]
+async fn self_loop_is_rejected_by_name() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![count_exector("a", &count), count_exector("x", &count)]);
+    manager.add_dep("x", "a");
+    manager.add_dep("x", "x");
+
+    let err = manager.validate().unwrap_err();
+    assert_eq!(err.to_string(), "find cycle: exector x depends on itself");
+    assert!(matches!(err, RunError::Cycle(path) if path == ["x", "x"]));
+    assert!(matches!(manager.run().await, Err(RunError::Cycle(_))));
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn optional_dep_only_waits_for_registered_exectors() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    for name in ["core", "report"] {
+        manager.add_exector(Box::new(LogExecutor {
+            name,
+            log: log.clone(),
+        }));
+    }
+    manager.add_dep("report", "core");
+    manager.add_optional_dep("report", "plugin");
+
+    manager.validate().unwrap();
+    manager.run().await.unwrap();
+    assert_eq!(*log.lock().unwrap(), vec!["core", "report"]);
+    assert_eq!(manager.deps("report"), vec!["core"]);
+
+    // a dep registered later is picked up
+    manager.add_exector(Box::new(LogExecutor {
+        name: "plugin",
+        log: log.clone(),
+    }));
+    manager.add_dep("plugin", "core");
+    assert_eq!(manager.deps("report"), vec!["core", "plugin"]);
+    log.lock().unwrap().clear();
+    manager.run().await.unwrap();
+    assert_eq!(*log.lock().unwrap(), vec!["core", "plugin", "report"]);
+
+    manager.remove_executor("plugin");
+    assert_eq!(manager.deps("report"), vec!["core"]);
+    manager.validate().unwrap();
+}
+
+struct BusyExecutor {
+    thread: Arc<Mutex<Option<std::thread::ThreadId>>>,
+}
+
+#[async_trait]
+impl Executor for BusyExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        *self.thread.lock().unwrap() = Some(std::thread::current().id());
+        std::thread::sleep(Duration::from_millis(100));
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        FastStr::from_static_str("busy")
+    }
+}
+
+#[tokio::test]
+async fn blocking_exector_leaves_async_exectors_running() {
+    let thread = Arc::new(Mutex::new(None));
+    let mut manager = Manager::new(1000);
+    manager.add_exector(Box::new(BusyExecutor {
+        thread: thread.clone(),
+    }));
+    manager.add_exector(Box::new(SleepExecutor {
+        name: "io",
+        sleep: Duration::from_millis(10),
+        finished: Arc::new(AtomicBool::new(false)),
+    }));
+    manager.set_blocking("busy");
+
+    let report = manager.run_with_report().await.unwrap();
+    assert!(report.is_success());
+    assert_ne!(thread.lock().unwrap().unwrap(), std::thread::current().id());
+    let (busy, io) = (
+        manager.node_timing("busy").unwrap(),
+        manager.node_timing("io").unwrap(),
+    );
+    assert!(io.end_micros < busy.end_micros);
+}
+
+#[test]
+fn graph_stats_describe_the_shape() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = diamond_manager(&count, None);
+    manager.add_exectors(vec![count_exector("e", &count), count_exector("f", &count)]);
+    manager.add_dep("e", "d");
+
+    assert_eq!(
+        manager.graph_stats().unwrap(),
+        GraphStats {
+            nodes: 6,
+            edges: 5,
+            max_fan_in: 2,
+            max_fan_out: 2,
+            longest_path_len: 3,
+            start_nodes: 2,
+            leaf_nodes: 2,
+        }
+    );
+
+    manager.add_dep("a", "e");
+    assert!(manager.graph_stats().is_err());
+}
+
+#[test]
+fn iter_topo_shows_nodes_without_running() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = diamond_manager(&count, None);
+    manager.set_priority("c", 3);
+    manager.set_node_meta("d", "team", "search");
+
+    let views: Vec<NodeView> = manager.iter_topo().unwrap().collect();
+    let names: Vec<&str> = views.iter().map(|view| view.name.as_str()).collect();
+    assert_eq!(names, vec!["a", "b", "c", "d"]);
+    assert_eq!(views[0].dependents, vec!["b", "c"]);
+    assert_eq!(views[2].priority, 3);
+    assert_eq!(views[3].deps, vec!["b", "c"]);
+    assert_eq!(
+        views[3].meta.get("team").map(String::as_str),
+        Some("search")
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+
+    manager.add_dep("a", "d");
+    assert!(manager.iter_topo().is_err());
+}
+
+#[tokio::test]
+async fn one_exector_runs_under_several_ids() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let fetch: Arc<dyn Executor> = Arc::new(CountExecutor {
+        name: "http_fetch",
+        count: count.clone(),
+    });
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    for name in ["users", "orders"] {
+        manager.add_exector(Box::new(LogExecutor {
+            name,
+            log: log.clone(),
+        }));
+    }
+    manager.add_shared_exector_as("fetch_users", fetch.clone());
+    manager.add_shared_exector_as("fetch_orders", fetch);
+    manager.add_dep("fetch_users", "users");
+    manager.add_dep("fetch_orders", "orders");
+    manager.set_node_timeout("fetch_orders", 500);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert_eq!(
+        report.completed(),
+        vec!["fetch_orders", "fetch_users", "orders", "users"]
+    );
+    assert!(!manager.has_exector("http_fetch"));
+    assert!(manager.node_timing("fetch_users").unwrap().start_micros > 0);
+}
+
+#[tokio::test]
+async fn zero_or_max_timeout_runs_without_limit() {
+    for timeout_ms in [0, u64::MAX] {
+        let finished = Arc::new(AtomicBool::new(false));
+        let mut manager = Manager::new(timeout_ms);
+        manager.add_exector(Box::new(SleepExecutor {
+            name: "batch",
+            sleep: Duration::from_millis(50),
+            finished: finished.clone(),
+        }));
+
+        manager.run().await.unwrap();
+        assert!(finished.load(Ordering::SeqCst));
+    }
+}
+
+#[tokio::test]
+async fn set_timeout_ms_applies_to_the_next_run() {
+    let mut manager = Manager::default();
+    manager.add_exector(Box::new(SleepExecutor {
+        name: "batch",
+        sleep: Duration::from_millis(50),
+        finished: Arc::new(AtomicBool::new(false)),
+    }));
+    manager.run().await.unwrap();
+
+    manager.set_timeout_ms(10);
+    let err = manager.run().await.unwrap_err();
+    assert!(matches!(err, RunError::Timeout { elapsed_ms: 10, .. }));
+
+    manager.set_timeout_ms(0);
+    manager.run().await.unwrap();
+}
+
+#[tokio::test]
+async fn failed_runs_tell_what_completed() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("fast", &count),
+        Box::new(FailExecutor { name: "broken" }),
+        Box::new(SleepExecutor {
+            name: "slow",
+            sleep: Duration::from_millis(200),
+            finished: Arc::new(AtomicBool::new(false)),
+        }),
+    ]);
+    manager.add_dep("broken", "fast");
+    let completed_names = |err: &RunError| {
+        err.completed()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    manager.set_failure_mode(FailureMode::StopOnError);
+    let err = manager.run().await.unwrap_err();
+    assert!(matches!(err, RunError::NodeFailed { .. }));
+    assert_eq!(completed_names(&err), vec!["fast"]);
+
+    manager.set_failure_mode(FailureMode::ContinueOnError);
+    manager.set_timeout_ms(50);
+    let err = manager.run().await.unwrap_err();
+    assert!(matches!(err, RunError::Timeout { .. }));
+    assert_eq!(completed_names(&err), vec!["fast"]);
+
+    manager.set_timeout_ms(1000);
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cancel.cancel();
+    });
+    let err = manager.run_with_cancel(token).await.unwrap_err();
+    assert!(matches!(err, RunError::Cancelled { .. }));
+    assert_eq!(completed_names(&err), vec!["fast"]);
+
+    let report = manager.run_with_report().await.unwrap();
+    let durations = report.completed_durations();
+    assert_eq!(durations.len(), 2);
+    assert_eq!(durations[1].0, "slow");
+    assert!(durations[1].1 >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn hints_order_ready_exectors_without_deps() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    manager.set_max_concurrency(1);
+    for name in ["a", "b", "c"] {
+        manager.add_exector(Box::new(LogExecutor {
+            name,
+            log: log.clone(),
+        }));
+    }
+    manager.add_hint("c", "a");
+    manager.add_hint("a", "b");
+    assert!(manager.deps("a").is_empty());
+
+    manager.run().await.unwrap();
+    assert_eq!(*log.lock().unwrap(), vec!["c", "a", "b"]);
+
+    // hints in a cycle still let the run finish
+    manager.add_hint("b", "c");
+    manager.validate().unwrap();
+    manager.run().await.unwrap();
+    assert_eq!(sorted_log(&log), vec!["a", "a", "b", "b", "c", "c"]);
+}
+
+#[tokio::test]
+async fn stop_branch_skips_only_the_pruned_branch() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.set_retry("poll", 3, Backoff::Fixed(Duration::from_millis(1)));
+    let polls = count.clone();
+    manager.add_exector(fn_executor("poll", move || {
+        polls.fetch_add(1, Ordering::SeqCst);
+        async { stop_branch() }
+    }));
+    for name in ["transform", "store", "config", "audit"] {
+        manager.add_exector(count_exector(name, &count));
+    }
+    manager.add_dep("transform", "poll");
+    manager.add_dep("store", "transform");
+    // audit also has a dep outside the stopped branch
+    manager.add_deps("audit", vec!["poll", "config"]);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.skipped(), vec!["store", "transform"]);
+    assert!(report.failed().is_empty());
+    assert!(matches!(
+        report.outcome("poll"),
+        Some(Outcome::Completed { .. })
+    ));
+    assert!(matches!(
+        report.outcome("audit"),
+        Some(Outcome::Completed { .. })
+    ));
+    // poll once, config and audit
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn dep_kinds_decide_per_edge_if_a_failure_propagates() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(FailExecutor { name: "fetch" }),
+        count_exector("parse", &count),
+        count_exector("cleanup", &count),
+        count_exector("plain", &count),
+    ]);
+    manager.add_dep_kind("parse", "fetch", DepKind::OnSuccess);
+    manager.add_dep_kind("cleanup", "fetch", DepKind::OnComplete);
+    manager.add_dep("plain", "fetch");
+
+    // by default a failed dep does not stop its dependents
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.skipped(), vec!["parse"]);
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    manager.set_failure_mode(FailureMode::SkipOnDepFailure);
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.skipped(), vec!["parse", "plain"]);
+    assert!(matches!(
+        report.outcome("cleanup"),
+        Some(Outcome::Completed { .. })
+    ));
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn try_add_edges_reports_the_first_bad_edge() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    for name in ["a", "b", "c", "d"] {
+        manager.add_exector(count_exector(name, &count));
+    }
+    manager.try_add_edges("a", vec!["b", "c"]).unwrap();
+    manager.try_add_deps("d", vec!["b", "c"]).unwrap();
+    // an edge that already exists is fine
+    manager.try_add_edge("a", "b").unwrap();
+
+    let err = manager.try_add_edges("d", vec!["e"]).unwrap_err();
+    assert_eq!(err.to_string(), "edge d -> e uses unknown exector e");
+    let err = manager.try_add_edge("b", "b").unwrap_err();
+    assert_eq!(err.to_string(), "edge b -> b is a self loop");
+
+    // the edge to c is taken back when the edge to a fails
+    let err = manager.try_add_edges("b", vec!["c", "a"]).unwrap_err();
+    assert_eq!(err.to_string(), "edge b -> a closes the cycle a -> b -> a");
+    assert_eq!(manager.dependents("b"), vec!["d"]);
+    let err = manager.try_add_deps("a", vec!["d"]).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("edge d -> a closes the cycle a -> "));
+    manager.validate().unwrap();
+}
+
+#[tokio::test]
+async fn start_order_is_stable_across_builds() {
+    let names = ["h", "c", "f", "a", "g", "d", "b", "e"];
+    let mut orders = Vec::new();
+    for _ in 0..5 {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = Manager::new(1000);
+        manager.set_max_concurrency(1);
+        for name in names {
+            manager.add_exector(Box::new(LogExecutor {
+                name,
+                log: log.clone(),
+            }));
+        }
+        assert_eq!(
+            manager.plan().unwrap(),
+            vec![vec!["a", "b", "c", "d", "e", "f", "g", "h"]]
+        );
+        manager.run().await.unwrap();
+        orders.push(log.lock().unwrap().clone());
+    }
+
+    assert_eq!(orders[0], vec!["a", "b", "c", "d", "e", "f", "g", "h"]);
+    assert!(orders.iter().all(|order| *order == orders[0]));
+}
+
+#[tokio::test]
+async fn exectors_run_on_the_given_runtime() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("graph-worker")
+        .enable_all()
+        .build()
+        .unwrap();
+    let threads = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    manager.set_runtime(runtime.handle().clone());
+    for name in ["a", "b"] {
+        let threads = threads.clone();
+        manager.add_exector(fn_executor(name, move || {
+            let threads = threads.clone();
+            async move {
+                let thread = std::thread::current().name().map(str::to_string);
+                threads.lock().unwrap().push(thread);
+                Ok(())
+            }
+        }));
+    }
+    manager.add_dep("b", "a");
+    manager.set_start_delay("b", Duration::from_millis(1));
+
+    manager.run().await.unwrap();
+    assert_eq!(
+        *threads.lock().unwrap(),
+        vec![Some("graph-worker".to_string()); 2]
+    );
+    // dropping a runtime inside another one panics
+    runtime.shutdown_background();
+}
+
+#[tokio::test]
+async fn non_critical_failures_do_not_stop_the_run() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(FailExecutor { name: "telemetry" }),
+        count_exector("load", &count),
+        count_exector("store", &count),
+    ]);
+    manager.add_dep_kind("load", "telemetry", DepKind::OnSuccess);
+    manager.add_dep("store", "load");
+    manager.set_non_critical("telemetry");
+    manager.set_failure_mode(FailureMode::StopOnError);
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert_eq!(report.failed_non_critical(), vec!["telemetry"]);
+    assert!(report.failed().is_empty());
+    assert!(report.first_error.is_none());
+    assert!(report.is_success());
+    assert!(matches!(
+        report.outcome("telemetry"),
+        Some(Outcome::FailedNonCritical(error)) if error.contains("telemetry failed")
+    ));
+
+    // a critical failure still stops the run
+    manager.add_exector(Box::new(FailExecutor { name: "core" }));
+    manager.add_dep("core", "store");
+    let err = manager.run().await.unwrap_err();
+    assert!(matches!(err, RunError::NodeFailed { ref name, .. } if name == "core"));
+}
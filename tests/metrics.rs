@@ -0,0 +1,50 @@
+#![cfg(feature = "prometheus")]
+
+mod common;
+
+use std::sync::{atomic::AtomicUsize, Arc};
+
+use common::*;
+use cycle_loader::manager::Manager;
+use prometheus::Registry;
+
+#[tokio::test]
+async fn run_updates_registered_metrics() {
+    let registry = Registry::new();
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.with_metrics_registry(&registry).unwrap();
+    manager.add_exectors(vec![
+        count_exector("a", &count),
+        Box::new(FailExecutor { name: "b" }),
+    ]);
+    manager.add_dep("b", "a");
+
+    manager.run().await.unwrap();
+    manager.run().await.unwrap();
+
+    let families = registry.gather();
+    let family = |name: &str| {
+        families
+            .iter()
+            .find(|family| family.name() == name)
+            .unwrap()
+    };
+
+    let durations = family("cycle_loader_node_duration_seconds");
+    assert_eq!(durations.get_metric().len(), 2);
+    assert!(durations
+        .get_metric()
+        .iter()
+        .all(|metric| metric.get_histogram().get_sample_count() == 2));
+
+    let failures = family("cycle_loader_node_failures_total");
+    assert_eq!(failures.get_metric().len(), 1);
+    assert_eq!(failures.get_metric()[0].get_label()[0].value(), "b");
+    assert_eq!(failures.get_metric()[0].get_counter().get_value(), 2.0);
+
+    let in_flight = family("cycle_loader_nodes_in_flight");
+    assert_eq!(in_flight.get_metric()[0].get_gauge().get_value(), 0.0);
+
+    assert!(Manager::new(1000).with_metrics_registry(&registry).is_err());
+}
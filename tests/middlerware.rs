@@ -0,0 +1,133 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use common::*;
+use cycle_loader::{
+    manager::Manager,
+    middlerware::{name_agnostic, Middlerware},
+    report::Outcome,
+    retry::Backoff,
+};
+
+fn logging_middlerware(tag: &'static str, log: &Arc<Mutex<Vec<String>>>) -> Middlerware {
+    let log = log.clone();
+    Box::new(move |name, exector, ctx| {
+        let log = log.clone();
+        Box::pin(async move {
+            log.lock().unwrap().push(format!("{} before {}", tag, name));
+            let res = exector.execute(&ctx).await;
+            log.lock().unwrap().push(format!("{} after {}", tag, name));
+            res
+        })
+    })
+}
+
+#[tokio::test]
+async fn middlerwares_wrap_in_order() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    manager.add_exector(count_exector("a", &count));
+    manager.add_middlerware(logging_middlerware("inner", &log));
+    manager.add_middlerware(logging_middlerware("outer", &log));
+
+    manager.run().await.unwrap();
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            "outer before a",
+            "inner before a",
+            "inner after a",
+            "outer after a"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn set_middlerware_replaces_added_ones() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    manager.add_exector(count_exector("a", &count));
+    manager.add_middlerware(logging_middlerware("old", &log));
+    manager.set_middlerware(logging_middlerware("new", &log));
+
+    manager.run().await.unwrap();
+    assert_eq!(*log.lock().unwrap(), vec!["new before a", "new after a"]);
+}
+
+#[tokio::test]
+async fn name_agnostic_middlerware() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![count_exector("a", &count), count_exector("b", &count)]);
+    let middlerware_calls = calls.clone();
+    manager.add_middlerware(name_agnostic(move |exector, ctx| {
+        middlerware_calls.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move { exector.execute(&ctx).await })
+    }));
+
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn failed_middlerware_setup_fails_the_node_without_retry() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let setups = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![count_exector("a", &count), count_exector("b", &count)]);
+    manager.set_retry("b", 3, Backoff::Fixed(Duration::from_millis(1)));
+    let setup_count = setups.clone();
+    manager.add_try_middlerware(Box::new(move |name, exector, ctx| {
+        setup_count.fetch_add(1, Ordering::SeqCst);
+        if name == "b" {
+            return Err(anyhow::anyhow!("pool unavailable").into());
+        }
+        Ok(Box::pin(async move { exector.execute(&ctx).await }))
+    }));
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(setups.load(Ordering::SeqCst), 2);
+    assert_eq!(report.failed(), vec!["b"]);
+    let Some(Outcome::Failed(error)) = report.outcome("b") else {
+        panic!("b should fail");
+    };
+    assert!(error.contains("middlerware setup failed for exector b: pool unavailable"));
+}
+
+#[tokio::test]
+async fn middlerware_can_short_circuit_the_exector() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![count_exector("a", &count), count_exector("b", &count)]);
+    manager.add_dep("b", "a");
+    manager.add_middlerware(Box::new(|name, exector, ctx| {
+        Box::pin(async move {
+            if name == "a" {
+                return Ok(());
+            }
+            exector.execute(&ctx).await
+        })
+    }));
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(report.outcome("a"), Some(&Outcome::Skipped));
+    assert!(matches!(
+        report.outcome("b"),
+        Some(Outcome::Completed { .. })
+    ));
+    assert!(manager.node_timing("a").unwrap().cached);
+    assert!(!manager.node_timing("b").unwrap().cached);
+}
@@ -0,0 +1,43 @@
+use std::sync::{Arc, Mutex};
+
+use cycle_loader::{define_nodes, exector::fn_executor, manager::Manager};
+
+define_nodes! { Load, Transform, Store }
+
+define_nodes! {
+    enum Stage { Fetch, Parse }
+}
+
+#[tokio::test]
+async fn typed_nodes_wire_the_graph() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    for node in Node::ALL {
+        let log = log.clone();
+        manager.add_exector(fn_executor(*node, move || {
+            let log = log.clone();
+            async move {
+                log.lock().unwrap().push(node.as_str());
+                Ok(())
+            }
+        }));
+    }
+    manager.add_dep(Node::Transform, Node::Load);
+    manager.add_dep(Node::Store, Node::Transform);
+    // the string api still reaches typed nodes
+    assert!(manager.has_exector("Store"));
+
+    manager.run().await.unwrap();
+    assert_eq!(*log.lock().unwrap(), vec!["Load", "Transform", "Store"]);
+}
+
+#[test]
+fn named_enum_keeps_variant_names() {
+    assert_eq!(Stage::ALL, &[Stage::Fetch, Stage::Parse]);
+    assert_eq!(Stage::Parse.to_string(), "Parse");
+}
+
+#[test]
+fn unknown_node_fails_to_compile() {
+    trybuild::TestCases::new().compile_fail("tests/ui/unknown_node.rs");
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use common::*;
+use cycle_loader::{
+    context::Context,
+    exector::{ExectorResult, Executor},
+    manager::Manager,
+    once::OnceRegistry,
+};
+use faststr::FastStr;
+
+struct InitExecutor {
+    count: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Executor for InitExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        self.count.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        FastStr::from_static_str("init")
+    }
+}
+
+fn graph(
+    init: &Arc<dyn Executor>,
+    registry: &Arc<OnceRegistry>,
+    count: &Arc<AtomicUsize>,
+) -> Manager {
+    let mut manager = Manager::new(1000);
+    manager.add_shared_exector(init.clone());
+    manager.add_exector(count_exector("work", count));
+    manager.add_dep("work", "init");
+    manager.set_run_once("init", registry.clone());
+    manager
+}
+
+#[tokio::test]
+async fn run_once_exector_executes_once_across_managers() {
+    let init_count = Arc::new(AtomicUsize::new(0));
+    let init: Arc<dyn Executor> = Arc::new(InitExecutor {
+        count: init_count.clone(),
+    });
+    let registry = Arc::new(OnceRegistry::new());
+    let work_count = Arc::new(AtomicUsize::new(0));
+    let mut first = graph(&init, &registry, &work_count);
+    let mut second = graph(&init, &registry, &work_count);
+
+    let (first_res, second_res) = tokio::join!(first.run(), second.run());
+    first_res.unwrap();
+    second_res.unwrap();
+    assert_eq!(init_count.load(Ordering::SeqCst), 1);
+    assert_eq!(work_count.load(Ordering::SeqCst), 2);
+    assert!(registry.has_run("init"));
+
+    second.run().await.unwrap();
+    assert_eq!(init_count.load(Ordering::SeqCst), 1);
+
+    registry.forget("init");
+    second.run().await.unwrap();
+    assert_eq!(init_count.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn failed_run_once_exector_is_tried_again() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let registry = Arc::new(OnceRegistry::new());
+    let mut manager = Manager::new(1000);
+    manager.add_exector(Box::new(FlakyExecutor {
+        name: "init",
+        fail_times: 1,
+        attempts: attempts.clone(),
+    }));
+    manager.set_run_once("init", registry.clone());
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.failed(), vec!["init"]);
+    assert!(!registry.has_run("init"));
+
+    manager.run().await.unwrap();
+    manager.run().await.unwrap();
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert!(registry.has_run("init"));
+}
@@ -0,0 +1,368 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use common::*;
+use cycle_loader::{
+    channel::{EventReceiver, OverflowPolicy},
+    context::Context,
+    exector::{ExectorResult, Executor},
+    manager::Manager,
+    middlerware::name_agnostic,
+    progress::{ProgressEvent, ProgressReporter},
+    report::NodeOutcome,
+};
+use faststr::FastStr;
+
+#[tokio::test]
+async fn progress_channel_streams_events_in_order() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("a", &count),
+        Box::new(FailExecutor { name: "b" }),
+    ]);
+    manager.add_dep("b", "a");
+    let mut progress = manager.progress_channel();
+
+    manager.run().await.unwrap();
+    let mut events = Vec::new();
+    while let Ok(event) = progress.try_recv() {
+        events.push(event);
+    }
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[0], ProgressEvent::Started("a".into()));
+    assert!(matches!(&events[1], ProgressEvent::Finished(name, _) if name == "a"));
+    assert_eq!(events[2], ProgressEvent::Started("b".into()));
+    assert!(
+        matches!(&events[3], ProgressEvent::Failed(name, error) if name == "b" && error.contains("b failed"))
+    );
+}
+
+#[tokio::test]
+async fn dropped_progress_receiver_does_not_stop_the_run() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exector(count_exector("a", &count));
+    drop(manager.progress_channel());
+
+    assert!(manager.run_with_report().await.unwrap().is_success());
+}
+
+#[tokio::test]
+async fn results_channel_delivers_outcomes_while_running() {
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(SleepExecutor {
+            name: "slow",
+            sleep: Duration::from_millis(100),
+            finished: Arc::new(AtomicBool::new(false)),
+        }),
+        Box::new(FailExecutor { name: "broken" }),
+    ]);
+    let mut results = manager.results_channel();
+
+    let consumer = tokio::spawn(async move {
+        let (name, outcome) = results.recv().await.unwrap();
+        // the first result arrives long before the slow exector is done
+        let first_at = Instant::now();
+        let (last_name, last_outcome) = results.recv().await.unwrap();
+        assert!(results.recv().await.is_none());
+        (name, outcome, last_name, last_outcome, first_at)
+    });
+    manager.run().await.unwrap();
+    let run_end = Instant::now();
+    drop(manager);
+
+    let (name, outcome, last_name, last_outcome, first_at) = consumer.await.unwrap();
+    assert_eq!(name, "broken");
+    assert!(!outcome.is_success());
+    assert_eq!(last_name, "slow");
+    assert!(last_outcome.is_success());
+    assert!(run_end - first_at >= Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn dropped_results_receiver_does_not_stop_the_run() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![count_exector("a", &count), count_exector("b", &count)]);
+    drop(manager.results_channel());
+
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
+
+struct StreamExecutor;
+
+#[async_trait]
+impl Executor for StreamExecutor {
+    async fn execute(&self, _ctx: &Context) -> ExectorResult {
+        Ok(())
+    }
+
+    async fn execute_with_progress(
+        &self,
+        _ctx: &Context,
+        progress: &ProgressReporter,
+    ) -> ExectorResult {
+        for percent in [0, 50, 100, 150] {
+            progress.report(percent);
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        FastStr::from_static_str("stream")
+    }
+}
+
+#[tokio::test]
+async fn exectors_report_progress_through_middlerwares() {
+    let mut manager = Manager::new(1000);
+    manager.add_exector(Box::new(StreamExecutor));
+    manager.add_middlerware(name_agnostic(|exector, ctx| {
+        Box::pin(async move { exector.execute(&ctx).await })
+    }));
+    let mut progress = manager.progress_channel();
+
+    manager.run().await.unwrap();
+    let mut events = Vec::new();
+    while let Ok(event) = progress.try_recv() {
+        events.push(event);
+    }
+    assert_eq!(events.len(), 6);
+    assert_eq!(
+        events[1..5],
+        [0, 50, 100, 100].map(|percent| ProgressEvent::NodeProgress("stream".into(), percent))
+    );
+    assert!(matches!(&events[5], ProgressEvent::Finished(name, _) if name == "stream"));
+}
+
+// a -> b -> c -> d, so results arrive in a fixed order
+fn chain_manager(count: &Arc<AtomicUsize>) -> Manager {
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("a", count),
+        count_exector("b", count),
+        count_exector("c", count),
+        count_exector("d", count),
+    ]);
+    manager.add_dep("b", "a");
+    manager.add_dep("c", "b");
+    manager.add_dep("d", "c");
+    manager
+}
+
+fn drain(results: &mut EventReceiver<(FastStr, NodeOutcome)>) -> Vec<FastStr> {
+    let mut names = Vec::new();
+    while let Some((name, _)) = results.try_recv() {
+        names.push(name);
+    }
+    names
+}
+
+#[tokio::test]
+async fn bounded_channel_drops_by_policy() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = chain_manager(&count);
+
+    let mut results = manager.bounded_results_channel(2, OverflowPolicy::DropEvent);
+    manager.run().await.unwrap();
+    assert_eq!(results.dropped(), 2);
+    assert_eq!(drain(&mut results), vec!["a", "b"]);
+
+    let mut results = manager.bounded_results_channel(2, OverflowPolicy::DropOldest);
+    manager.run().await.unwrap();
+    assert_eq!(results.dropped(), 2);
+    assert_eq!(drain(&mut results), vec!["c", "d"]);
+}
+
+// a -> b -> c -> d taking 20ms each, results come out 20ms apart
+fn slow_chain_manager() -> Manager {
+    let finished = Arc::new(AtomicBool::new(false));
+    let mut manager = Manager::new(1000);
+    for name in ["a", "b", "c", "d"] {
+        manager.add_exector(Box::new(SleepExecutor {
+            name,
+            sleep: Duration::from_millis(20),
+            finished: finished.clone(),
+        }));
+    }
+    manager.add_dep("b", "a");
+    manager.add_dep("c", "b");
+    manager.add_dep("d", "c");
+    manager
+}
+
+// takes a, then is busy while b, c and d arrive
+async fn slow_consumer(mut results: EventReceiver<(FastStr, NodeOutcome)>) -> (Vec<FastStr>, u64) {
+    let mut names = Vec::new();
+    while let Some((name, _)) = results.recv().await {
+        names.push(name);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    (names, results.dropped())
+}
+
+#[tokio::test]
+async fn bounded_channel_drops_by_policy_for_a_slow_consumer() {
+    let cases = [
+        (OverflowPolicy::DropEvent, vec!["a", "b", "c"]),
+        (OverflowPolicy::DropOldest, vec!["a", "c", "d"]),
+    ];
+    for (policy, expected) in cases {
+        let mut manager = slow_chain_manager();
+        let results = manager.bounded_results_channel(2, policy);
+        let consumer = tokio::spawn(slow_consumer(results));
+        let started = Instant::now();
+        manager.run().await.unwrap();
+        // the run did not wait for the consumer
+        assert!(started.elapsed() < Duration::from_millis(200));
+        drop(manager);
+
+        let (names, dropped) = consumer.await.unwrap();
+        assert_eq!(names, expected);
+        assert_eq!(dropped, 1);
+    }
+}
+
+#[tokio::test]
+async fn bounded_channel_blocks_the_run_for_a_slow_consumer() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = chain_manager(&count);
+    let mut results = manager.bounded_results_channel(
+        1,
+        OverflowPolicy::Block {
+            stall_timeout: None,
+        },
+    );
+
+    let consumer = tokio::spawn(async move {
+        let mut names = Vec::new();
+        while let Some((name, _)) = results.recv().await {
+            names.push(name);
+            tokio::time::sleep(Duration::from_millis(30)).await;
+        }
+        (names, results.dropped())
+    });
+    let started = Instant::now();
+    manager.run().await.unwrap();
+    // the run waited for the consumer to take a, b and c
+    assert!(started.elapsed() >= Duration::from_millis(60));
+    drop(manager);
+
+    let (names, dropped) = consumer.await.unwrap();
+    assert_eq!(names, vec!["a", "b", "c", "d"]);
+    assert_eq!(dropped, 0);
+}
+
+#[tokio::test]
+async fn blocking_channel_stall_timeout_for_a_consumer_slower_than_it() {
+    // the consumer is busy with a for 200ms, b fills the channel
+    let cases = [
+        (Some(Duration::from_millis(50)), vec!["a", "b"], 2),
+        (None, vec!["a", "b", "c", "d"], 0),
+    ];
+    for (stall_timeout, expected, expected_dropped) in cases {
+        let mut manager = slow_chain_manager();
+        let results = manager.bounded_results_channel(1, OverflowPolicy::Block { stall_timeout });
+        let consumer = tokio::spawn(slow_consumer(results));
+        let started = Instant::now();
+        manager.run().await.unwrap();
+        if stall_timeout.is_some() {
+            // the run gave up on the consumer instead of waiting for it
+            assert!(started.elapsed() < Duration::from_millis(200));
+        } else {
+            // the run waited for the consumer to take a, b and c
+            assert!(started.elapsed() >= Duration::from_millis(400));
+        }
+        drop(manager);
+
+        let (names, dropped) = consumer.await.unwrap();
+        assert_eq!(names, expected);
+        assert_eq!(dropped, expected_dropped);
+    }
+}
+
+#[tokio::test]
+async fn blocking_channel_never_holds_more_than_its_capacity() {
+    let concurrency = Concurrency::default();
+    let mut manager = Manager::new(1000);
+    for name in LEAF_NAMES {
+        manager.add_exector(Box::new(PeakExecutor {
+            name,
+            concurrency: concurrency.clone(),
+        }));
+    }
+    let mut progress = manager.bounded_progress_channel(
+        2,
+        OverflowPolicy::Block {
+            stall_timeout: None,
+        },
+    );
+
+    let consumer = tokio::spawn(async move {
+        let (mut events, mut longest) = (0, 0);
+        while progress.recv().await.is_some() {
+            events += 1;
+            longest = longest.max(progress.len());
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        (events, longest, progress.dropped())
+    });
+    manager.run().await.unwrap();
+    drop(manager);
+
+    let (events, longest, dropped) = consumer.await.unwrap();
+    assert_eq!(events, 2 * LEAF_NAMES.len());
+    assert!(longest <= 2);
+    assert_eq!(dropped, 0);
+    // a full channel held back starts, without it every leaf starts at once
+    assert!(concurrency.peak.load(Ordering::SeqCst) < LEAF_NAMES.len());
+}
+
+#[tokio::test]
+async fn blocking_channel_read_after_the_run_falls_back_to_dropping() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = chain_manager(&count);
+    manager.set_timeout_ms(0);
+    let mut progress = manager.bounded_progress_channel(
+        2,
+        OverflowPolicy::Block {
+            stall_timeout: Some(Duration::from_millis(100)),
+        },
+    );
+
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 4);
+    assert_eq!(progress.dropped(), 6);
+    assert_eq!(
+        progress.try_recv(),
+        Some(ProgressEvent::Started("a".into()))
+    );
+    assert!(matches!(progress.try_recv(), Some(ProgressEvent::Finished(name, _)) if name == "a"));
+    assert_eq!(progress.try_recv(), None);
+}
+
+#[tokio::test]
+async fn blocking_channel_without_a_receiver_does_not_stop_the_run() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = chain_manager(&count);
+    drop(manager.bounded_progress_channel(
+        1,
+        OverflowPolicy::Block {
+            stall_timeout: None,
+        },
+    ));
+
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 4);
+}
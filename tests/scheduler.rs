@@ -0,0 +1,175 @@
+mod common;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use async_trait::async_trait;
+use common::*;
+use cycle_loader::{
+    context::Context,
+    exector::{ExectorResult, Executor},
+    manager::Manager,
+    scheduler::Scheduler,
+};
+use faststr::FastStr;
+
+// schedules a page for every link, each depending on the page that found it and named after it
+struct Page {
+    name: FastStr,
+    links: Vec<Vec<&'static str>>,
+    visited: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Executor for Page {
+    async fn execute(&self, ctx: &Context) -> ExectorResult {
+        self.visited.fetch_add(1, Ordering::SeqCst);
+        let scheduler = ctx.get::<Scheduler>().unwrap();
+        let mut links = self.links.clone();
+        if links.is_empty() {
+            return Ok(());
+        }
+        for link in links.remove(0) {
+            let page = Page {
+                name: format!("{}/{}", self.name, link).into(),
+                links: links.clone(),
+                visited: self.visited.clone(),
+            };
+            scheduler
+                .schedule(Box::new(page), vec![self.name.clone()])
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        self.name.clone()
+    }
+}
+
+#[tokio::test]
+async fn scheduled_exectors_run_in_the_same_run() {
+    let visited = Arc::new(AtomicUsize::new(0));
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(Page {
+            name: "root".into(),
+            links: vec![vec!["a", "b"], vec!["c"]],
+            visited: visited.clone(),
+        }),
+        count_exector("index", &count),
+    ]);
+    manager.add_dep("index", "root");
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(visited.load(Ordering::SeqCst), 5);
+    assert!(report.is_success());
+    assert_eq!(
+        report.completed(),
+        vec!["index", "root", "root/a", "root/a/c", "root/b", "root/b/c"]
+    );
+    assert!(manager.has_exector("root/a/c"));
+
+    // gone before the next run schedules them again
+    assert!(manager.run_with_report().await.unwrap().is_success());
+    assert_eq!(visited.load(Ordering::SeqCst), 10);
+    manager.reset();
+    assert!(!manager.has_exector("root/a"));
+    assert!(manager.has_exector("index"));
+}
+
+// tries to schedule a child with the given deps and records the error
+struct BadParent {
+    child: &'static str,
+    deps: Vec<&'static str>,
+    errors: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl Executor for BadParent {
+    async fn execute(&self, ctx: &Context) -> ExectorResult {
+        let scheduler = ctx.get::<Scheduler>().unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        let child = count_exector(self.child, &count);
+        if let Err(err) = scheduler.schedule(child, self.deps.clone()).await {
+            self.errors.lock().unwrap().push(err.to_string());
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> FastStr {
+        "parent".into()
+    }
+}
+
+#[tokio::test]
+async fn scheduling_checks_names_and_deps() {
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let count = Arc::new(AtomicUsize::new(0));
+    let cases = [
+        ("child", vec!["first"]),
+        ("child", vec!["missing"]),
+        ("first", vec!["parent"]),
+    ];
+    for (child, deps) in cases {
+        let mut manager = Manager::new(1000);
+        manager.add_exectors(vec![
+            count_exector("first", &count),
+            Box::new(BadParent {
+                child,
+                deps,
+                errors: errors.clone(),
+            }),
+        ]);
+        manager.add_dep("parent", "first");
+        assert!(manager.run_with_report().await.unwrap().is_success());
+        assert!(!manager.has_exector("child"));
+    }
+
+    assert_eq!(
+        *errors.lock().unwrap(),
+        vec![
+            "scheduled exector child depends on first, which already completed",
+            "scheduled exector child depends on missing, which is not part of the run",
+            "exector name repeat: first",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn scheduling_over_the_edge_limit_fails() {
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let mut manager = Manager::new(1000);
+    manager.set_limits(10, 0);
+    manager.add_exector(Box::new(BadParent {
+        child: "child",
+        deps: vec!["parent"],
+        errors: errors.clone(),
+    }));
+
+    assert!(manager.run_with_report().await.unwrap().is_success());
+    assert!(!manager.has_exector("child"));
+    assert_eq!(
+        *errors.lock().unwrap(),
+        vec!["edge parent -> child exceeds the limit of 0 edges"]
+    );
+}
+
+#[tokio::test]
+async fn scheduling_after_the_run_fails() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exector(count_exector("a", &count));
+    manager.run().await.unwrap();
+
+    let scheduler = manager.context().get::<Scheduler>().unwrap();
+    let err = scheduler
+        .schedule(count_exector("late", &count), Vec::<FastStr>::new())
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "run is over, nothing can be scheduled");
+    assert!(!manager.has_exector("late"));
+}
@@ -0,0 +1,258 @@
+mod common;
+
+use std::sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use common::*;
+use cycle_loader::manager::Manager;
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Metadata, Subscriber,
+};
+
+#[derive(Debug, Clone)]
+struct RecordedSpan {
+    name: &'static str,
+    parent: Option<u64>,
+    fields: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct Recorded {
+    next_id: AtomicU64,
+    spans: Mutex<Vec<RecordedSpan>>,
+    events: Mutex<Vec<Vec<(String, String)>>>,
+    entered: Mutex<Vec<u64>>,
+}
+
+// keep every span with its parent and recorded fields, enough to check the span tree
+#[derive(Clone, Default)]
+struct SpanRecorder(Arc<Recorded>);
+
+struct FieldVisitor<'a>(&'a mut Vec<(String, String)>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .push((field.name().to_string(), format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name().to_string(), value.to_string()));
+    }
+}
+
+impl Subscriber for SpanRecorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let id = self.0.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let parent = if let Some(parent) = attrs.parent() {
+            Some(parent.into_u64())
+        } else if attrs.is_contextual() {
+            self.0.entered.lock().unwrap().last().copied()
+        } else {
+            None
+        };
+
+        let mut fields = Vec::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        self.0.spans.lock().unwrap().push(RecordedSpan {
+            name: attrs.metadata().name(),
+            parent,
+            fields,
+        });
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut spans = self.0.spans.lock().unwrap();
+        let span = &mut spans[span.into_u64() as usize - 1];
+        values.record(&mut FieldVisitor(&mut span.fields));
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Vec::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        self.0.events.lock().unwrap().push(fields);
+    }
+
+    fn enter(&self, span: &Id) {
+        self.0.entered.lock().unwrap().push(span.into_u64());
+    }
+
+    fn exit(&self, _span: &Id) {
+        self.0.entered.lock().unwrap().pop();
+    }
+}
+
+impl RecordedSpan {
+    fn field(&self, name: &str) -> Option<&str> {
+        field(&self.fields, name)
+    }
+}
+
+fn field<'a>(fields: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    fields
+        .iter()
+        .find(|(field, _)| field == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn span_manager(count: &Arc<AtomicUsize>) -> Manager {
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("a", count),
+        Box::new(FailExecutor { name: "b" }),
+    ]);
+    manager.add_dep("b", "a");
+    manager
+}
+
+#[tokio::test]
+async fn exector_spans_are_children_of_the_run_span() {
+    let recorder = SpanRecorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = span_manager(&count);
+    manager.set_span_enabled(true);
+    manager.run().await.unwrap();
+
+    let spans = recorder.0.spans.lock().unwrap().clone();
+    assert_eq!(spans.len(), 3);
+    assert_eq!(spans[0].name, "run");
+    for span in &spans[1..] {
+        assert_eq!(span.name, "executor");
+        assert_eq!(span.parent, Some(1));
+        assert!(span.field("start_us").is_some());
+        assert!(span.field("end_us").is_some());
+        assert_eq!(span.field("attempts"), Some("1"));
+    }
+
+    let error_span = spans
+        .iter()
+        .find(|span| span.field("name") == Some("b"))
+        .unwrap();
+    assert!(error_span.field("error").unwrap().contains("b failed"));
+}
+
+#[tokio::test]
+async fn spans_are_off_by_default() {
+    let recorder = SpanRecorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = span_manager(&count);
+    manager.run().await.unwrap();
+
+    assert!(recorder.0.spans.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn events_carry_node_fields() {
+    let recorder = SpanRecorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = span_manager(&count);
+    manager.run().await.unwrap();
+
+    let events = recorder.0.events.lock().unwrap().clone();
+    let node_event = |node: &str| {
+        events
+            .iter()
+            .find(|fields| field(fields, "node") == Some(node))
+            .unwrap()
+    };
+    let done = node_event("a");
+    assert_eq!(field(done, "status"), Some("done"));
+    assert_eq!(field(done, "attempts"), Some("1"));
+    assert!(field(done, "dur_us").is_some());
+    let failed = node_event("b");
+    assert_eq!(field(failed, "status"), Some("failed"));
+    assert!(field(failed, "error").unwrap().contains("b failed"));
+
+    let run = events
+        .iter()
+        .find(|fields| field(fields, "message") == Some("run finished"))
+        .unwrap();
+    assert_eq!(field(run, "completed"), Some("1"));
+    assert_eq!(field(run, "failed"), Some("1"));
+    let summary = |events: &[Vec<(String, String)>]| {
+        events.iter().any(|fields| {
+            field(fields, "message")
+                .is_some_and(|message| message.starts_with("exector tracing info"))
+        })
+    };
+    assert!(!summary(&events));
+
+    manager.set_log_summary(true);
+    manager.run().await.unwrap();
+    assert!(summary(&recorder.0.events.lock().unwrap()));
+}
+
+#[tokio::test]
+async fn labeled_runs_tag_events_and_report() {
+    let recorder = SpanRecorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = span_manager(&count);
+    manager.set_span_enabled(true);
+    let report = manager.run_labeled("nightly-42").await.unwrap();
+    assert_eq!(report.run_id, "nightly-42");
+    assert_eq!(manager.run_id(), "nightly-42");
+
+    let events = recorder.0.events.lock().unwrap().clone();
+    assert!(!events.is_empty());
+    assert!(events
+        .iter()
+        .all(|fields| field(fields, "run_id") == Some("nightly-42")));
+    let spans = recorder.0.spans.lock().unwrap().clone();
+    assert_eq!(spans[0].field("run_id"), Some("nightly-42"));
+
+    // unlabeled runs get a fresh id each
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(report.run_id.len(), 32);
+    manager.run().await.unwrap();
+    assert_ne!(manager.run_id(), report.run_id);
+}
+
+#[tokio::test]
+async fn dropping_an_unrun_manager_warns() {
+    let recorder = SpanRecorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+    let dropped_warnings = || {
+        recorder
+            .0
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|fields| {
+                field(fields, "message") == Some("manager dropped without running its exectors")
+            })
+            .count()
+    };
+
+    let count = Arc::new(AtomicUsize::new(0));
+    drop(span_manager(&count));
+    assert_eq!(dropped_warnings(), 1);
+
+    let mut manager = span_manager(&count);
+    manager.set_warn_unrun_drop(false);
+    drop(manager);
+    let mut manager = span_manager(&count);
+    manager.run().await.unwrap();
+    drop(manager);
+    drop(Manager::new(1000));
+    assert_eq!(dropped_warnings(), 1);
+}
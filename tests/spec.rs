@@ -0,0 +1,62 @@
+mod common;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use common::*;
+use cycle_loader::{manager::Manager, spec::GraphSpec};
+
+fn spec(edges: &[(&str, &str)]) -> GraphSpec {
+    GraphSpec {
+        nodes: vec!["a".into(), "b".into(), "c".into()],
+        edges: edges
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect(),
+    }
+}
+
+#[tokio::test]
+async fn apply_spec_wires_registered_exectors() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        count_exector("a", &count),
+        count_exector("b", &count),
+        count_exector("c", &count),
+    ]);
+    manager
+        .apply_spec(&spec(&[("a", "b"), ("b", "c")]))
+        .unwrap();
+
+    assert_eq!(
+        manager.plan().unwrap(),
+        vec![vec!["a"], vec!["b"], vec!["c"]]
+    );
+    manager.run().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn apply_spec_rejects_undeclared_nodes() {
+    let mut manager = Manager::new(1000);
+    let err = manager
+        .apply_spec(&spec(&[("a", "b"), ("x", "c"), ("a", "y")]))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "edges reference undeclared nodes: a -> y, x -> c"
+    );
+    assert!(manager.topo_order().unwrap().is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn graph_spec_round_trips_through_serde() {
+    let json = r#"{"nodes":["a","b","c"],"edges":[["a","b"],["a","c"]]}"#;
+    let parsed: GraphSpec = serde_json::from_str(json).unwrap();
+    assert_eq!(parsed, spec(&[("a", "b"), ("a", "c")]));
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+}
@@ -0,0 +1,291 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use common::*;
+use cycle_loader::{
+    exector::{fn_executor, Executor},
+    manager::Manager,
+    report::RunStats,
+    tracing_info::{Clock, RunCounts, Status},
+};
+
+#[tokio::test]
+async fn tracing_snapshot_exposes_node_timings() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        Box::new(SleepExecutor {
+            name: "slow",
+            sleep: Duration::from_millis(20),
+            finished: Arc::new(AtomicBool::new(false)),
+        }),
+        count_exector("fast", &count),
+        Box::new(FailExecutor { name: "broken" }),
+    ]);
+    manager.add_dep("fast", "slow");
+
+    let snapshot = manager.tracing_snapshot();
+    assert_eq!(
+        snapshot
+            .iter()
+            .map(|timing| timing.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["broken", "fast", "slow"]
+    );
+    assert!(snapshot
+        .iter()
+        .all(|timing| timing.status == Status::NotStarted && timing.duration().is_none()));
+
+    manager.run().await.unwrap();
+    let snapshot = manager.tracing_snapshot();
+    assert_eq!(snapshot[0].status, Status::Failed);
+    assert_eq!(snapshot[1].status, Status::Done);
+    assert_eq!(snapshot[2].status, Status::Done);
+    assert!(snapshot[2].duration().unwrap() >= Duration::from_millis(20));
+    assert!(snapshot[1].start_micros >= snapshot[2].end_micros);
+
+    let slow = manager.node_timing("slow").unwrap();
+    assert_eq!(slow, snapshot[2]);
+    assert_eq!(slow.attempts, 1);
+    assert!(manager.node_timing("missing").is_err());
+}
+
+fn sleep_exector(name: &'static str, millis: u64) -> Box<SleepExecutor> {
+    Box::new(SleepExecutor {
+        name,
+        sleep: Duration::from_millis(millis),
+        finished: Arc::new(AtomicBool::new(false)),
+    })
+}
+
+#[tokio::test]
+async fn critical_path_follows_longest_chain() {
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![
+        sleep_exector("a", 10),
+        sleep_exector("b", 60),
+        sleep_exector("c", 5),
+        sleep_exector("d", 10),
+    ]);
+    manager.add_deps("d", vec!["b", "c"]);
+    manager.add_deps("b", vec!["a"]);
+    manager.add_deps("c", vec!["a"]);
+    assert!(manager.critical_path().is_err());
+
+    manager.run().await.unwrap();
+    let (path, total) = manager.critical_path().unwrap();
+    assert_eq!(path, vec!["a", "b", "d"]);
+    assert!(total >= Duration::from_millis(80));
+}
+
+#[tokio::test]
+async fn wait_time_is_separate_from_run_time() {
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![sleep_exector("a", 30), sleep_exector("b", 30)]);
+    manager.set_max_concurrency(1);
+
+    manager.run().await.unwrap();
+    let mut waits: Vec<_> = manager
+        .tracing_snapshot()
+        .iter()
+        .map(|timing| {
+            assert!(timing.duration().unwrap() >= Duration::from_millis(30));
+            timing.wait().unwrap()
+        })
+        .collect();
+    waits.sort();
+    assert!(waits[0] < Duration::from_millis(10));
+    assert!(waits[1] >= Duration::from_millis(30));
+}
+
+#[tokio::test]
+async fn start_delay_counts_as_wait() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exectors(vec![count_exector("a", &count), count_exector("b", &count)]);
+    manager.add_dep("b", "a");
+    manager.set_start_delay("b", Duration::from_millis(30));
+
+    manager.run().await.unwrap();
+    let a = manager.node_timing("a").unwrap();
+    let b = manager.node_timing("b").unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert!(b.ready_micros >= a.end_micros);
+    assert!(b.start_micros - a.end_micros >= 30_000);
+    assert!(b.wait().unwrap() >= Duration::from_millis(30));
+    assert!(b.duration().unwrap() < Duration::from_millis(30));
+}
+
+#[derive(Default)]
+struct MockClock(AtomicI64);
+
+impl Clock for MockClock {
+    fn now_micros(&self) -> i64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+fn advancing_exector(name: &'static str, clock: &Arc<MockClock>, micros: i64) -> Box<dyn Executor> {
+    let clock = clock.clone();
+    fn_executor(name, move || {
+        clock.0.fetch_add(micros, Ordering::SeqCst);
+        async { Ok(()) }
+    })
+}
+
+#[tokio::test]
+async fn added_exectors_are_traced_before_they_run() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.add_exector(count_exector("a", &count));
+    assert_eq!(manager.node_timing("a").unwrap().status, Status::NotStarted);
+    assert!(manager.node_timing("missing").is_err());
+
+    manager.run().await.unwrap();
+    // added after a run, still traced by the next one
+    manager.add_exector(count_exector("b", &count));
+    assert_eq!(manager.node_timing("b").unwrap().status, Status::NotStarted);
+    manager.run().await.unwrap();
+    for name in ["a", "b"] {
+        let timing = manager.node_timing(name).unwrap();
+        assert_eq!(timing.status, Status::Done);
+        assert!(timing.end_micros >= timing.start_micros);
+    }
+}
+
+#[tokio::test]
+async fn mock_clock_gives_exact_timings() {
+    let clock = Arc::new(MockClock::default());
+    let mut manager = Manager::new(1000);
+    manager.set_clock(clock.clone());
+    manager.add_exectors(vec![
+        advancing_exector("a", &clock, 5_000),
+        advancing_exector("b", &clock, 7_000),
+    ]);
+    manager.add_dep("b", "a");
+
+    manager.run().await.unwrap();
+    let a = manager.node_timing("a").unwrap();
+    assert_eq!((a.start_micros, a.end_micros), (0, 5_000));
+    let b = manager.node_timing("b").unwrap();
+    assert_eq!((b.start_micros, b.end_micros), (5_000, 12_000));
+    assert_eq!(
+        manager.critical_path().unwrap(),
+        (vec!["a".into(), "b".into()], Duration::from_millis(12))
+    );
+
+    let stats = manager.run_stats();
+    assert_eq!(stats.wall_clock, Duration::from_millis(12));
+    assert_eq!(stats.total_node_time, Duration::from_millis(12));
+    assert_eq!(stats.parallelism, 1.0);
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn report_round_trips_through_serde() {
+    use cycle_loader::report::RunReport;
+
+    let clock = Arc::new(MockClock::default());
+    let mut manager = Manager::new(1000);
+    manager.set_clock(clock.clone());
+    let panicking_clock = clock.clone();
+    manager.add_exectors(vec![
+        advancing_exector("a", &clock, 5_000),
+        advancing_exector("b", &clock, 7_000),
+        // a panicked exector has no attempts, the report still counts it
+        fn_executor("c", move || {
+            panicking_clock.0.fetch_add(3_000, Ordering::SeqCst);
+            async move { panic!("bad input") }
+        }),
+    ]);
+    manager.add_dep("b", "a");
+    manager.add_dep("c", "b");
+
+    let report = manager.run_labeled("nightly").await.unwrap();
+    assert_eq!(report.failed(), vec!["c"]);
+    assert!(!report.attempts.contains_key("c"));
+    let json = serde_json::to_string(&report).unwrap();
+    assert!(json.contains(r#""a":{"Completed":{"start":0,"end":5000}}"#));
+
+    let parsed: RunReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.outcomes, report.outcomes);
+    assert_eq!(parsed.run_id, "nightly");
+    assert_eq!(parsed.attempts, report.attempts);
+    assert_eq!(parsed.deps, report.deps);
+    assert_eq!(parsed.failed_spans, report.failed_spans);
+    assert_eq!(
+        parsed.critical_path().unwrap(),
+        manager.critical_path().unwrap()
+    );
+    assert_eq!(
+        parsed.critical_path().unwrap(),
+        (
+            vec!["a".into(), "b".into(), "c".into()],
+            Duration::from_micros(15_000)
+        )
+    );
+    assert_eq!(parsed.stats(), manager.run_stats());
+    assert_eq!(
+        serde_json::to_string(&parsed.stats()).unwrap(),
+        r#"{"wall_clock":15000,"total_node_time":15000,"parallelism":1.0}"#
+    );
+    assert!(RunReport::default().critical_path().is_err());
+}
+
+#[tokio::test]
+async fn run_stats_measure_parallelism() {
+    let mut manager = Manager::new(1000);
+    assert_eq!(manager.run_stats(), RunStats::default());
+    manager.add_exectors(vec![
+        sleep_exector("a", 50),
+        sleep_exector("b", 50),
+        sleep_exector("c", 50),
+    ]);
+
+    manager.run().await.unwrap();
+    let stats = manager.run_stats();
+    assert!(stats.wall_clock >= Duration::from_millis(50));
+    assert!(stats.total_node_time >= Duration::from_millis(150));
+    assert!(stats.parallelism > 1.5);
+}
+
+#[tokio::test]
+async fn disabled_tracing_keeps_only_counts() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut manager = Manager::new(1000);
+    manager.set_tracing(false);
+    manager.add_exectors(vec![
+        count_exector("a", &count),
+        count_exector("b", &count),
+        Box::new(FailExecutor { name: "c" }),
+    ]);
+    manager.add_dep("b", "a");
+
+    let report = manager.run_with_report().await.unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert!(report.outcomes.is_empty());
+    assert!(manager.tracing_snapshot().is_empty());
+    assert!(manager.node_timing("a").is_err());
+    assert_eq!(
+        manager.run_counts(),
+        RunCounts {
+            done: 2,
+            failed: 1,
+            skipped: 0,
+            cancelled: 0,
+            in_progress: 0,
+        }
+    );
+
+    manager.set_tracing(true);
+    manager.run().await.unwrap();
+    assert_eq!(manager.node_timing("a").unwrap().status, Status::Done);
+    assert_eq!(manager.run_counts().done, 2);
+}
@@ -0,0 +1,6 @@
+cycle_loader::define_nodes! { Load, Store }
+
+fn main() {
+    let mut manager = cycle_loader::manager::Manager::default();
+    manager.add_dep(Node::Store, Node::Laod);
+}